@@ -0,0 +1,436 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pluggable resolution of the ledger entries, contract Wasm, and restore
+//! preambles a [`crate::types::SimulationRequest`] doesn't supply inline.
+//!
+//! Today a request forces the caller to inline everything: `ledger_entries`
+//! as a prefetched `HashMap<String, String>`, `contract_wasm` as a blob,
+//! `wasm_path` for local files, `restore_preamble` as raw JSON. Anything
+//! missing is simply absent from the simulation. [`LedgerSource`] (blocking)
+//! and [`AsyncLedgerSource`] (async) let a request instead name a
+//! [`DataSourceConfig`] and have the simulator lazily fetch whatever's
+//! missing -- mirroring the common split between a blocking "send and
+//! retry" RPC client and an async "fire and forget" one that the caller
+//! awaits on its own schedule.
+//!
+//! [`RpcLedgerSource`] is the network-backed default; [`HashMapLedgerSource`]
+//! is a no-network stand-in for tests that never retries. `RpcLedgerSource`
+//! retries transient failures per a [`RetryPolicy`] via [`retry_blocking`];
+//! [`retry_async`] is the same loop for async callers.
+
+use base64::Engine as _;
+use serde::Deserialize;
+use soroban_env_host::xdr::{Hash, LedgerEntryData, LedgerKey, LedgerKeyContractCode, Limits, ReadXdr, WriteXdr};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+/// Every way resolving an entry from a [`LedgerSource`]/[`AsyncLedgerSource`]
+/// can fail.
+#[derive(Debug)]
+pub enum DataSourceError {
+    /// The underlying fetch kept failing until `RetryPolicy::max_attempts`
+    /// was exhausted; `last_error` is the final attempt's failure. Also
+    /// reported with `attempts: 0` for a failure outside the retried fetch
+    /// (an invalid hash, a key that won't encode, an entry that won't decode).
+    Exhausted { attempts: u32, last_error: String },
+}
+
+impl std::fmt::Display for DataSourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataSourceError::Exhausted { attempts, last_error } => {
+                write!(f, "gave up after {attempts} attempt(s): {last_error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DataSourceError {}
+
+/// Blocking variant of the data-source abstraction: resolves one entry per
+/// call. Intended for the synchronous `simulate` entry point, mirroring a
+/// blocking "send and retry" RPC client -- implementations are expected to
+/// retry transient failures internally (e.g. via [`retry_blocking`]) rather
+/// than surfacing them to the caller on the first blip.
+pub trait LedgerSource {
+    /// Resolves a ledger entry by its base64 `LedgerKey` XDR, returning its
+    /// base64 `LedgerEntry` XDR -- the same encoding `SimulationRequest::ledger_entries` uses.
+    fn get_ledger_entry(&self, key_xdr_b64: &str) -> Result<Option<String>, DataSourceError>;
+    /// Resolves a contract's Wasm bytecode by its code hash, hex-encoded.
+    fn get_contract_wasm(&self, wasm_hash_hex: &str) -> Result<Option<Vec<u8>>, DataSourceError>;
+    /// Resolves the restore preamble (the set of archived entries a
+    /// transaction's footprint requires restoring before it can run), if
+    /// the source tracks one.
+    fn get_restore_preamble(&self) -> Result<Option<serde_json::Value>, DataSourceError>;
+}
+
+/// Async counterpart of [`LedgerSource`], for callers (e.g. a future
+/// `--serve` handler) that want to resolve several missing entries
+/// concurrently instead of blocking the handling thread on each one in turn.
+#[async_trait::async_trait]
+pub trait AsyncLedgerSource {
+    async fn get_ledger_entry(&self, key_xdr_b64: &str) -> Result<Option<String>, DataSourceError>;
+    async fn get_contract_wasm(&self, wasm_hash_hex: &str) -> Result<Option<Vec<u8>>, DataSourceError>;
+    async fn get_restore_preamble(&self) -> Result<Option<serde_json::Value>, DataSourceError>;
+}
+
+/// Exponential backoff between retries of a transient fetch failure.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub backoff_multiplier: u32,
+}
+
+impl RetryPolicy {
+    /// Three attempts, starting at 100ms and doubling -- enough to ride out
+    /// a blip in an RPC endpoint without stalling a simulation for long.
+    pub fn default_for_rpc() -> Self {
+        Self { max_attempts: 3, initial_backoff: Duration::from_millis(100), backoff_multiplier: 2 }
+    }
+
+    fn backoff_for_attempt(&self, attempt: u32) -> Duration {
+        self.initial_backoff * self.backoff_multiplier.saturating_pow(attempt)
+    }
+}
+
+/// Runs `fetch` under `policy`, sleeping with exponential backoff between
+/// failures and returning [`DataSourceError::Exhausted`] once
+/// `max_attempts` is reached.
+pub fn retry_blocking<T>(policy: &RetryPolicy, mut fetch: impl FnMut() -> Result<T, String>) -> Result<T, DataSourceError> {
+    let mut last_error = String::new();
+    for attempt in 0..policy.max_attempts {
+        match fetch() {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                last_error = e;
+                if attempt + 1 < policy.max_attempts {
+                    thread::sleep(policy.backoff_for_attempt(attempt));
+                }
+            }
+        }
+    }
+    Err(DataSourceError::Exhausted { attempts: policy.max_attempts, last_error })
+}
+
+/// Async counterpart of [`retry_blocking`], sleeping via `tokio::time::sleep`
+/// between attempts instead of blocking the executor thread.
+pub async fn retry_async<T, F, Fut>(policy: &RetryPolicy, mut fetch: F) -> Result<T, DataSourceError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, String>>,
+{
+    let mut last_error = String::new();
+    for attempt in 0..policy.max_attempts {
+        match fetch().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                last_error = e;
+                if attempt + 1 < policy.max_attempts {
+                    tokio::time::sleep(policy.backoff_for_attempt(attempt)).await;
+                }
+            }
+        }
+    }
+    Err(DataSourceError::Exhausted { attempts: policy.max_attempts, last_error })
+}
+
+/// No-network [`LedgerSource`] backed entirely by in-memory maps, for tests
+/// and offline runs -- nothing here is ever transient, so it never retries.
+#[derive(Debug, Clone, Default)]
+pub struct HashMapLedgerSource {
+    pub ledger_entries: HashMap<String, String>,
+    pub contract_wasm: HashMap<String, Vec<u8>>,
+    pub restore_preamble: Option<serde_json::Value>,
+}
+
+impl HashMapLedgerSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LedgerSource for HashMapLedgerSource {
+    fn get_ledger_entry(&self, key_xdr_b64: &str) -> Result<Option<String>, DataSourceError> {
+        Ok(self.ledger_entries.get(key_xdr_b64).cloned())
+    }
+
+    fn get_contract_wasm(&self, wasm_hash_hex: &str) -> Result<Option<Vec<u8>>, DataSourceError> {
+        Ok(self.contract_wasm.get(wasm_hash_hex).cloned())
+    }
+
+    fn get_restore_preamble(&self) -> Result<Option<serde_json::Value>, DataSourceError> {
+        Ok(self.restore_preamble.clone())
+    }
+}
+
+/// [`LedgerSource`] backed by a Soroban RPC endpoint, retrying transient
+/// failures per `policy`. Every lookup, contract Wasm included (as a
+/// `ContractCode` ledger entry), is a `getLedgerEntries` call of the same
+/// shape as `crate::rpc` in the `erst-simulator`
+/// binary; this lives in the library crate as its own client so it doesn't
+/// need a dependency on that binary-only module.
+pub struct RpcLedgerSource {
+    pub rpc_url: String,
+    pub timeout_ms: u64,
+    pub policy: RetryPolicy,
+}
+
+impl RpcLedgerSource {
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        Self { rpc_url: rpc_url.into(), timeout_ms: 5_000, policy: RetryPolicy::default_for_rpc() }
+    }
+
+    /// Calls `getLedgerEntries` for a single key, base64-encoded XDR in, at
+    /// most one base64 XDR entry out -- the same JSON-RPC shape `crate::rpc`
+    /// uses in the binary, duplicated here rather than shared since that
+    /// module is binary-only.
+    fn fetch_ledger_entry_xdr(&self, key_xdr_b64: &str) -> Result<Option<String>, String> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getLedgerEntries",
+            "params": { "keys": [key_xdr_b64] },
+        });
+
+        let response: RpcResponse = ureq::post(&self.rpc_url)
+            .timeout(Duration::from_millis(self.timeout_ms))
+            .send_json(body)
+            .map_err(|e| e.to_string())?
+            .into_json()
+            .map_err(|e| format!("invalid RPC response: {e}"))?;
+
+        if let Some(err) = response.error {
+            return Err(err.message);
+        }
+
+        Ok(response.result.and_then(|r| r.entries).unwrap_or_default().into_iter().next().map(|e| e.xdr))
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<RpcResult>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RpcResult {
+    entries: Option<Vec<RpcLedgerEntryResult>>,
+}
+
+#[derive(Deserialize)]
+struct RpcLedgerEntryResult {
+    xdr: String,
+}
+
+impl LedgerSource for RpcLedgerSource {
+    fn get_ledger_entry(&self, key_xdr_b64: &str) -> Result<Option<String>, DataSourceError> {
+        let key_xdr_b64 = key_xdr_b64.to_string();
+        retry_blocking(&self.policy, || self.fetch_ledger_entry_xdr(&key_xdr_b64))
+    }
+
+    fn get_contract_wasm(&self, wasm_hash_hex: &str) -> Result<Option<Vec<u8>>, DataSourceError> {
+        let hash_bytes: [u8; 32] = hex::decode(wasm_hash_hex)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| DataSourceError::Exhausted {
+                attempts: 0,
+                last_error: format!("invalid contract Wasm hash: {wasm_hash_hex}"),
+            })?;
+        let key = LedgerKey::ContractCode(LedgerKeyContractCode { hash: Hash(hash_bytes) });
+        let key_xdr_b64 = key
+            .to_xdr(Limits::none())
+            .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+            .map_err(|e| DataSourceError::Exhausted { attempts: 0, last_error: format!("failed to encode LedgerKey: {e:?}") })?;
+
+        let entry_xdr_b64 = retry_blocking(&self.policy, || self.fetch_ledger_entry_xdr(&key_xdr_b64))?;
+        let Some(entry_xdr_b64) = entry_xdr_b64 else {
+            return Ok(None);
+        };
+
+        let entry_bytes = base64::engine::general_purpose::STANDARD
+            .decode(&entry_xdr_b64)
+            .map_err(|e| DataSourceError::Exhausted { attempts: 0, last_error: format!("invalid entry XDR base64: {e}") })?;
+        let entry = soroban_env_host::xdr::LedgerEntry::from_xdr(entry_bytes, Limits::none())
+            .map_err(|e| DataSourceError::Exhausted { attempts: 0, last_error: format!("invalid entry XDR: {e:?}") })?;
+
+        match entry.data {
+            LedgerEntryData::ContractCode(code_entry) => Ok(Some(code_entry.code.to_vec())),
+            _ => Ok(None),
+        }
+    }
+
+    fn get_restore_preamble(&self) -> Result<Option<serde_json::Value>, DataSourceError> {
+        // Soroban RPC exposes a restore preamble only as part of a
+        // `simulateTransaction` response, not as a standalone lookup by key
+        // -- there's nothing for an `RpcLedgerSource` to fetch here.
+        Ok(None)
+    }
+}
+
+/// Selector a [`crate::types::SimulationRequest`] carries to pick which
+/// [`LedgerSource`] backs on-demand resolution of entries it didn't supply
+/// inline -- so a missing entry is fetched instead of the simulation simply
+/// erroring or running against incomplete state.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DataSourceConfig {
+    /// Fetch from a Soroban RPC endpoint, retrying transient failures.
+    Rpc {
+        url: String,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+        #[serde(default)]
+        retry: Option<RetryPolicyConfig>,
+    },
+    /// No [`LedgerSource`] is consulted: entries come from the request's own
+    /// fields, and a missing entry isn't fetched through this abstraction.
+    /// The default. The `erst-simulator` binary still fetches the invoked
+    /// contract's instance and code entries from the request's legacy
+    /// `rpc_url`, when one is set, under this variant.
+    Inline,
+}
+
+impl Default for DataSourceConfig {
+    fn default() -> Self {
+        DataSourceConfig::Inline
+    }
+}
+
+/// The wire-format counterpart of [`RetryPolicy`]; `Duration` doesn't
+/// implement `Deserialize` directly.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RetryPolicyConfig {
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub backoff_multiplier: u32,
+}
+
+impl From<RetryPolicyConfig> for RetryPolicy {
+    fn from(cfg: RetryPolicyConfig) -> Self {
+        Self {
+            max_attempts: cfg.max_attempts,
+            initial_backoff: Duration::from_millis(cfg.initial_backoff_ms),
+            backoff_multiplier: cfg.backoff_multiplier,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashmap_source_resolves_present_entries() {
+        let mut source = HashMapLedgerSource::new();
+        source.ledger_entries.insert("key-b64".to_string(), "entry-b64".to_string());
+        source.contract_wasm.insert("deadbeef".to_string(), vec![0x00, 0x61, 0x73, 0x6d]);
+        source.restore_preamble = Some(serde_json::json!({"min_resource_fee": 100}));
+
+        assert_eq!(source.get_ledger_entry("key-b64").unwrap(), Some("entry-b64".to_string()));
+        assert_eq!(source.get_contract_wasm("deadbeef").unwrap(), Some(vec![0x00, 0x61, 0x73, 0x6d]));
+        assert_eq!(source.get_restore_preamble().unwrap(), Some(serde_json::json!({"min_resource_fee": 100})));
+    }
+
+    #[test]
+    fn test_hashmap_source_missing_entries_are_none_not_err() {
+        let source = HashMapLedgerSource::new();
+        assert_eq!(source.get_ledger_entry("missing").unwrap(), None);
+        assert_eq!(source.get_contract_wasm("missing").unwrap(), None);
+        assert_eq!(source.get_restore_preamble().unwrap(), None);
+    }
+
+    #[test]
+    fn test_retry_blocking_succeeds_without_exhausting_attempts() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(0),
+            backoff_multiplier: 2,
+        };
+        let mut calls = 0;
+        let result = retry_blocking(&policy, || {
+            calls += 1;
+            if calls < 3 {
+                Err("transient".to_string())
+            } else {
+                Ok(42)
+            }
+        });
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_retry_blocking_exhausts_after_max_attempts() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(0),
+            backoff_multiplier: 2,
+        };
+        let mut calls = 0;
+        let result: Result<(), DataSourceError> = retry_blocking(&policy, || {
+            calls += 1;
+            Err("still failing".to_string())
+        });
+        match result {
+            Err(DataSourceError::Exhausted { attempts, last_error }) => {
+                assert_eq!(attempts, 3);
+                assert_eq!(last_error, "still failing");
+            }
+            Ok(()) => panic!("expected exhaustion"),
+        }
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_retry_policy_backoff_doubles_per_attempt() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(100),
+            backoff_multiplier: 2,
+        };
+        assert_eq!(policy.backoff_for_attempt(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for_attempt(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff_for_attempt(2), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_retry_policy_config_converts_to_duration_based_policy() {
+        let cfg = RetryPolicyConfig { max_attempts: 5, initial_backoff_ms: 250, backoff_multiplier: 3 };
+        let policy: RetryPolicy = cfg.into();
+        assert_eq!(policy.max_attempts, 5);
+        assert_eq!(policy.initial_backoff, Duration::from_millis(250));
+        assert_eq!(policy.backoff_multiplier, 3);
+    }
+
+    #[test]
+    fn test_data_source_config_defaults_to_inline() {
+        assert!(matches!(DataSourceConfig::default(), DataSourceConfig::Inline));
+    }
+
+    #[test]
+    fn test_data_source_config_deserializes_rpc_variant() {
+        let json = serde_json::json!({
+            "kind": "rpc",
+            "url": "https://rpc.example.com",
+            "timeout_ms": 8000,
+        });
+        let config: DataSourceConfig = serde_json::from_value(json).unwrap();
+        match config {
+            DataSourceConfig::Rpc { url, timeout_ms, retry } => {
+                assert_eq!(url, "https://rpc.example.com");
+                assert_eq!(timeout_ms, Some(8000));
+                assert!(retry.is_none());
+            }
+            DataSourceConfig::Inline => panic!("expected Rpc variant"),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! A typed error for [`crate::simulate`], replacing the ad-hoc
+//! `String`/`StructuredError` errors it used to hand back. Each variant
+//! carries exactly the context its `Display` impl needs, and maps to a
+//! stable [`SimError::code`] a caller can match on instead of parsing the
+//! rendered message.
+
+use serde::Serialize;
+
+/// Every way a simulation can fail before or during contract execution.
+/// Rendered into the response as [`ResponseError`] via [`SimError::into_response_error`].
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SimError {
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(String),
+    #[error("Failed to decode {field} Base64: {source}")]
+    Base64Decode { field: &'static str, source: base64::DecodeError },
+    #[error("Failed to parse {kind} XDR: {source}")]
+    XdrParse { kind: &'static str, source: soroban_env_host::xdr::Error },
+    #[error("Failed to inject ledger entries into host storage: {0}")]
+    StorageInjection(String),
+    #[error("Contract execution trapped: {message}")]
+    HostTrap { code: String, message: String },
+    #[error("Contract execution panicked: {0}")]
+    Panic(String),
+}
+
+impl SimError {
+    /// Stable, machine-readable identifier for this variant, independent of
+    /// `message`'s wording.
+    fn code(&self) -> &'static str {
+        match self {
+            SimError::InvalidJson(_) => "INVALID_JSON",
+            SimError::Base64Decode { .. } => "BASE64_DECODE",
+            SimError::XdrParse { .. } => "XDR_PARSE",
+            SimError::StorageInjection(_) => "STORAGE_INJECTION",
+            SimError::HostTrap { .. } => "HOST_TRAP",
+            SimError::Panic(_) => "PANIC",
+        }
+    }
+
+    fn details(&self) -> Option<String> {
+        match self {
+            SimError::Base64Decode { field, .. } => Some(format!("field: {}", field)),
+            SimError::XdrParse { kind, .. } => Some(format!("xdr_kind: {}", kind)),
+            SimError::HostTrap { code, .. } => Some(format!("host_error_code: {}", code)),
+            SimError::InvalidJson(_) | SimError::StorageInjection(_) | SimError::Panic(_) => None,
+        }
+    }
+
+    /// Converts into the serializable shape carried by `SimulationResponse::error`.
+    pub(crate) fn into_response_error(self) -> ResponseError {
+        ResponseError { code: self.code().to_string(), message: self.to_string(), details: self.details() }
+    }
+}
+
+/// Maps a host-level execution failure into [`SimError::HostTrap`]. The
+/// host's `Error` carries a type/code pair (e.g. contract error code, host
+/// function index) rather than a message, so both the `code` and the
+/// formatted error go into the response for the caller to branch on.
+pub(crate) fn host_trap_from(host_error: &soroban_env_host::HostError) -> SimError {
+    SimError::HostTrap {
+        code: format!("{:?}", host_error.error),
+        message: format!("{:?}", host_error),
+    }
+}
+
+/// The JSON shape carried by `SimulationResponse::error`:
+/// `{"code": "...", "message": ..., "details": ...}`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ResponseError {
+    pub(crate) code: String,
+    pub(crate) message: String,
+    pub(crate) details: Option<String>,
+}
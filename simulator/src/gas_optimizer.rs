@@ -0,0 +1,111 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Heuristic advisor that turns a [`crate::types::BudgetUsage`] reading into
+//! a short list of plain-English suggestions for trimming a contract
+//! invocation's CPU/memory footprint -- surfaced via
+//! [`crate::types::SimulationResponse::optimization_report`] when a request
+//! opts in via `enable_optimization_advisor`.
+
+use serde::Serialize;
+
+/// How urgently [`OptimizationSuggestion::category`] should be acted on.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// One actionable observation about the invocation's resource usage.
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizationSuggestion {
+    pub severity: Severity,
+    pub category: String,
+    pub message: String,
+    /// Rough fraction of the consumed budget this suggestion could claw
+    /// back, when estimable; `None` when it's advisory only (e.g. "you're
+    /// close to the limit") rather than tied to a specific fix.
+    pub estimated_savings_percent: Option<f64>,
+}
+
+/// The full set of suggestions produced for one invocation.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct OptimizationReport {
+    pub suggestions: Vec<OptimizationSuggestion>,
+}
+
+/// Usage percentages above which the advisor flags a resource as
+/// approaching its limit -- high enough to not nag on routine invocations,
+/// low enough to give a caller room to react before a run actually fails.
+const HIGH_USAGE_WARNING_PERCENT: f64 = 75.0;
+const HIGH_USAGE_CRITICAL_PERCENT: f64 = 95.0;
+
+impl OptimizationReport {
+    /// Builds a report from CPU/memory usage percentages (as in
+    /// [`crate::types::BudgetUsage`]) -- a pure function over the two
+    /// numbers rather than the whole `BudgetUsage` so it's trivial to test
+    /// without constructing a full budget reading.
+    pub fn from_usage(cpu_usage_percent: f64, memory_usage_percent: f64) -> Self {
+        let mut suggestions = Vec::new();
+
+        push_usage_suggestion(&mut suggestions, "cpu", cpu_usage_percent);
+        push_usage_suggestion(&mut suggestions, "memory", memory_usage_percent);
+
+        Self { suggestions }
+    }
+}
+
+fn push_usage_suggestion(suggestions: &mut Vec<OptimizationSuggestion>, resource: &str, usage_percent: f64) {
+    let severity = if usage_percent >= HIGH_USAGE_CRITICAL_PERCENT {
+        Severity::Critical
+    } else if usage_percent >= HIGH_USAGE_WARNING_PERCENT {
+        Severity::Warning
+    } else {
+        return;
+    };
+
+    suggestions.push(OptimizationSuggestion {
+        severity,
+        category: format!("{resource}_usage"),
+        message: format!(
+            "{resource} usage is at {usage_percent:.1}% of budget; consider reducing {resource} \
+             consumption (e.g. fewer host calls, smaller footprint) before raising the limit"
+        ),
+        estimated_savings_percent: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_usage_below_threshold_has_no_suggestions() {
+        let report = OptimizationReport::from_usage(10.0, 20.0);
+        assert!(report.suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_from_usage_warns_above_warning_threshold() {
+        let report = OptimizationReport::from_usage(80.0, 10.0);
+        assert_eq!(report.suggestions.len(), 1);
+        assert_eq!(report.suggestions[0].severity, Severity::Warning);
+        assert_eq!(report.suggestions[0].category, "cpu_usage");
+    }
+
+    #[test]
+    fn test_from_usage_critical_above_critical_threshold() {
+        let report = OptimizationReport::from_usage(10.0, 97.0);
+        assert_eq!(report.suggestions.len(), 1);
+        assert_eq!(report.suggestions[0].severity, Severity::Critical);
+        assert_eq!(report.suggestions[0].category, "memory_usage");
+    }
+
+    #[test]
+    fn test_from_usage_flags_both_resources_independently() {
+        let report = OptimizationReport::from_usage(99.0, 80.0);
+        assert_eq!(report.suggestions.len(), 2);
+    }
+}
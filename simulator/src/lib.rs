@@ -3,11 +3,12 @@
 
 #![allow(clippy::pedantic, clippy::nursery, dead_code)]
 
+pub mod data_source;
 pub mod gas_optimizer;
-pub mod git_detector;
-pub mod snapshot;
+pub mod manifest;
 pub mod source_map_cache;
 pub mod source_mapper;
 pub mod stack_trace;
 pub mod types;
+pub mod vm;
 pub mod wasm_types;
@@ -4,164 +4,636 @@
 use base64::Engine as _;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use soroban_env_host::budget::Budget;
 use soroban_env_host::events::Events;
-use soroban_env_host::xdr::ReadXdr;
+use soroban_env_host::storage::{SnapshotSource, Storage};
+use soroban_env_host::xdr::{
+    LedgerEntry, LedgerEntryData, LedgerInfo, LedgerKey, ReadXdr, ScAddress, ScVal, WriteXdr,
+};
+use soroban_env_host::{Host, HostError};
 use std::collections::HashMap;
 use std::io::{self, Read};
 use std::panic;
+use std::rc::Rc;
+
+mod error;
+mod rpc;
+mod server;
+
+use error::{host_trap_from, ResponseError, SimError};
+use simulator::manifest::Manifest;
+
+/// Recursion cap for [`scval_to_json`]: a `Vec`/`Map` nested deeper than this
+/// falls back to `raw_xdr` rather than risking a stack overflow on a
+/// maliciously (or just very deeply) nested contract value.
+const SCVAL_JSON_DEPTH_LIMIT: usize = 16;
+
+/// Testnet passphrase used to derive `LedgerInfo::network_id` when a
+/// request doesn't specify its own, so out-of-the-box simulations line up
+/// with `testnet`.
+const DEFAULT_NETWORK_PASSPHRASE: &str = "Test SDF Network ; September 2015";
 
 #[derive(Debug, Deserialize)]
-struct SimulationRequest {
+pub(crate) struct SimulationRequest {
     envelope_xdr: String,
     result_meta_xdr: String,
     ledger_entries: Option<HashMap<String, String>>,
+    /// Ledger sequence the invocation should observe; defaults to 0 (no
+    /// network round trip is made to discover the "real" current ledger).
+    ledger_sequence: Option<u32>,
+    /// Close time of `ledger_sequence`, in Unix seconds.
+    ledger_timestamp: Option<u64>,
+    /// Network passphrase used to derive `LedgerInfo::network_id`.
+    network_passphrase: Option<String>,
+    base_reserve: Option<u32>,
+    /// Soroban RPC endpoint to fetch the target contract's instance and
+    /// Wasm code entries from, for any of those two keys `ledger_entries`
+    /// didn't already supply. No other request is made against it.
+    rpc_url: Option<String>,
+    /// RPC round-trip timeout; defaults to [`rpc::DEFAULT_TIMEOUT_MS`].
+    rpc_timeout_ms: Option<u64>,
+    /// When `true`, `rpc_url` is ignored and no network access is made, for
+    /// reproducible offline runs.
+    offline: Option<bool>,
+    /// Which [`simulator::data_source::LedgerSource`] `augment_snapshot_from_rpc`
+    /// fetches missing entries from; defaults to
+    /// [`simulator::data_source::DataSourceConfig::Inline`], which falls
+    /// back to the `rpc_url`/`rpc_timeout_ms` fields above instead of
+    /// dispatching through a `LedgerSource`. Set this to `DataSourceConfig::Rpc`
+    /// to fetch (with retry/backoff) through the shared data-source
+    /// abstraction instead.
+    #[serde(default)]
+    data_source: simulator::data_source::DataSourceConfig,
+    /// Name of a section (e.g. `"testnet"`, `"mainnet"`) in the manifest
+    /// named by `$ERST_MANIFEST_PATH` to fill any of this request's unset
+    /// fields (`rpc_url`, `mock_base_fee`, `mock_gas_price`,
+    /// `resource_calibration`, `enable_optimization_advisor`, `profile`)
+    /// from. `None` (the default) leaves every field exactly as supplied,
+    /// and no manifest is read.
+    #[serde(default)]
+    environment: Option<String>,
+    /// Flat base fee that, together with `mock_gas_price`, replaces this
+    /// build's approximate fee schedule: the resource fee becomes
+    /// `mock_base_fee + mock_gas_price * cpu_instructions`. Ignored unless
+    /// both are set.
+    mock_base_fee: Option<u32>,
+    /// Per-CPU-instruction price paired with `mock_base_fee`; see there.
+    mock_gas_price: Option<u64>,
+    /// Empirically-fitted crypto-op cost coefficients (see
+    /// [`simulator::types::ResourceCalibration::from_measurements`]) used in
+    /// place of the hard-coded instruction-fee constant when set.
+    resource_calibration: Option<simulator::types::ResourceCalibration>,
+    /// When `true`, runs the recorded budget usage through
+    /// [`simulator::gas_optimizer::OptimizationReport::from_usage`] and
+    /// attaches the result as `optimization_report`.
+    #[serde(default)]
+    enable_optimization_advisor: Option<bool>,
+    /// Reserved for a future profiling pass; currently only recorded in
+    /// `logs` as "requested but not implemented" since no profiler exists
+    /// in this build yet.
+    profile: Option<bool>,
+    /// Controls over which events `categorized_events` includes and how
+    /// they're shaped; defaults to unfiltered, uncapped, `pretty` output.
+    events: Option<EventQuery>,
 }
 
-#[derive(Debug, Serialize, Clone)]
-struct CategorizedEvent {
-    event_type: String,
+/// Mirrors a typical event query against a batch of simulated transactions:
+/// filter by contract, by event type, and by a topic-symbol prefix, cap the
+/// number returned, and pick an output shape.
+#[derive(Debug, Deserialize, Clone, Default)]
+struct EventQuery {
+    /// Keep only events from this contract, as a `C...` strkey.
     contract_id: Option<String>,
-    topics: Vec<String>,
-    data: String,
+    /// Keep only events of this type: `"contract"`, `"system"`, or `"diagnostic"`.
+    event_type: Option<String>,
+    /// Keep only events whose first topic is a `Symbol` starting with this prefix.
+    topic_prefix: Option<String>,
+    /// Stop after this many matching events.
+    count: Option<usize>,
+    #[serde(default)]
+    format: EventFormat,
+}
+
+/// Output shape for `categorized_events`: `Pretty` is a keyed JSON object
+/// per event; `Compact` is a positional JSON array, cheaper to transmit
+/// across a large batch at the cost of needing the field order memorized.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum EventFormat {
+    #[default]
+    Pretty,
+    Compact,
 }
 
 #[derive(Debug, Serialize)]
-struct SimulationResponse {
+pub(crate) struct SimulationResponse {
     status: String,
-    error: Option<String>,
+    error: Option<ResponseError>,
     events: Vec<String>,
-    categorized_events: Vec<CategorizedEvent>,
+    categorized_events: serde_json::Value,
     logs: Vec<String>,
+    /// Base64 XDR of the `ScVal` the invoked contract function returned, when
+    /// execution reached a `HostFunction::InvokeContract` and succeeded.
+    result_xdr: Option<String>,
+    /// The same return value as `result_xdr`, decoded into structured JSON
+    /// via [`scval_to_json`] for callers that don't want to parse XDR.
+    result: Option<serde_json::Value>,
+    /// Budget consumption and a projected resource fee, computed locally
+    /// from this invocation's recorded footprint instead of a network round
+    /// trip to `simulateTransaction`.
+    resource_estimate: Option<ResourceEstimate>,
+    /// Suggestions for trimming the invocation's CPU/memory footprint,
+    /// derived from `resource_estimate`'s consumption via
+    /// [`simulator::gas_optimizer::OptimizationReport::from_usage`]. Only
+    /// populated when `request.enable_optimization_advisor` is `true`.
+    optimization_report: Option<simulator::gas_optimizer::OptimizationReport>,
 }
 
-fn categorize_event_for_analyzer(
-    event: &soroban_env_host::events::HostEvent,
-) -> Result<String, String> {
-    use soroban_env_host::xdr::{ContractEventBody, ContractEventType, ScVal};
+/// CPU/memory/ledger-I/O consumption recorded during one invocation, plus a
+/// projected resource fee using the same decomposition as the network's
+/// `simulateTransaction`: a per-instruction rate, a per-byte rate for
+/// memory and for reads, a (pricier) per-byte rate for writes, and a
+/// bandwidth component proportional to the submitted transaction's size.
+#[derive(Debug, Serialize, Clone, Copy)]
+struct ResourceEstimate {
+    cpu_instructions: u64,
+    memory_bytes: u64,
+    read_entries: u32,
+    write_entries: u32,
+    read_bytes: u64,
+    write_bytes: u64,
+    resource_fee: u64,
+}
 
-    let contract_id = match &event.event.contract_id {
-        Some(id) => format!("{:?}", id),
-        None => "unknown".to_string(),
-    };
+/// An in-memory [`SnapshotSource`] backed by the `ledger_entries` the caller
+/// supplied in the request, so `Storage` can resolve reads against exactly
+/// the state the caller provided instead of hitting the network.
+struct InMemorySnapshot {
+    entries: HashMap<LedgerKey, Rc<LedgerEntry>>,
+}
 
-    let event_type_str = match &event.event.type_ {
-        ContractEventType::Contract => "contract",
-        ContractEventType::System => "system",
-        ContractEventType::Diagnostic => "diagnostic",
-    };
+impl SnapshotSource for InMemorySnapshot {
+    fn get(&self, key: &Rc<LedgerKey>) -> Result<Option<(Rc<LedgerEntry>, Option<u32>)>, HostError> {
+        // `None` for live-until: the caller-supplied snapshot doesn't carry
+        // TTL bookkeeping, so entries are treated as always live.
+        Ok(self.entries.get(key.as_ref()).cloned().map(|entry| (entry, None)))
+    }
+}
 
-    let (topics, _data_val) = match &event.event.body {
-        ContractEventBody::V0(v0) => (&v0.topics, &v0.data),
-    };
+/// Derives `LedgerInfo::network_id` the same way Stellar core does: the
+/// SHA-256 digest of the network passphrase.
+fn network_id_from_passphrase(passphrase: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(passphrase.as_bytes());
+    hasher.finalize().into()
+}
 
-    let event_json = if let Some(first_topic) = topics.get(0) {
-        let topic_str = format!("{:?}", first_topic);
+fn build_ledger_info(request: &SimulationRequest) -> LedgerInfo {
+    let passphrase = request.network_passphrase.as_deref().unwrap_or(DEFAULT_NETWORK_PASSPHRASE);
+    LedgerInfo {
+        protocol_version: 21,
+        sequence_number: request.ledger_sequence.unwrap_or(0),
+        timestamp: request.ledger_timestamp.unwrap_or(0),
+        network_id: network_id_from_passphrase(passphrase),
+        base_reserve: request.base_reserve.unwrap_or(5_000_000),
+        min_temp_entry_ttl: 16 * 60 * 60 / 5,
+        min_persistent_entry_ttl: 30 * 24 * 60 * 60 / 5,
+        max_entry_ttl: 365 * 24 * 60 * 60 / 5,
+    }
+}
 
-        if topic_str.contains("require_auth") {
-            let address = if let ScVal::Address(addr) = first_topic {
-                format!("{:?}", addr)
-            } else {
-                "unknown".to_string()
+/// Resolves one missing ledger entry at a time, backed by whichever source
+/// `request` selects: [`simulator::data_source::DataSourceConfig::Rpc`]
+/// dispatches through a [`simulator::data_source::LedgerSource`] (with its
+/// own retry/backoff); [`simulator::data_source::DataSourceConfig::Inline`]
+/// (the default) falls back to the legacy `rpc_url`/`rpc_timeout_ms` fields
+/// via the hand-rolled `rpc` client.
+enum EntryFetcher<'a> {
+    DataSource(Box<dyn simulator::data_source::LedgerSource>),
+    LegacyRpc { rpc_url: &'a str, timeout_ms: u64 },
+}
+
+impl EntryFetcher<'_> {
+    fn fetch(&self, key: &LedgerKey) -> Result<Option<LedgerEntry>, String> {
+        match self {
+            EntryFetcher::DataSource(source) => {
+                let key_xdr = key.to_xdr(soroban_env_host::xdr::Limits::none()).map_err(|e| format!("{e:?}"))?;
+                let key_b64 = base64::engine::general_purpose::STANDARD.encode(key_xdr);
+                let Some(entry_b64) = source.get_ledger_entry(&key_b64).map_err(|e| e.to_string())? else {
+                    return Ok(None);
+                };
+                let entry_bytes = base64::engine::general_purpose::STANDARD
+                    .decode(&entry_b64)
+                    .map_err(|e| format!("invalid entry XDR base64: {e}"))?;
+                LedgerEntry::from_xdr(entry_bytes, soroban_env_host::xdr::Limits::none())
+                    .map(Some)
+                    .map_err(|e| format!("invalid entry XDR: {e:?}"))
+            }
+            EntryFetcher::LegacyRpc { rpc_url, timeout_ms } => {
+                rpc::fetch_ledger_entries(rpc_url, std::slice::from_ref(key), *timeout_ms)
+                    .map(|entries| entries.into_iter().next())
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+/// When `request` names a data source (or, absent that, `request.rpc_url`)
+/// and `request.offline` isn't `true`, fetches the invoked contract's
+/// instance entry (and, if the instance names a Wasm executable, its code
+/// entry) and merges whichever of those two keys `snapshot_entries` doesn't
+/// already have.
+///
+/// Only these two keys are backfilled -- an arbitrary storage read the
+/// invocation turns out to need isn't knowable ahead of time without first
+/// running (part of) the invocation, so those still surface as ordinary
+/// "entry not found" failures from `Storage` if the caller's `ledger_entries`
+/// didn't happen to cover them.
+fn augment_snapshot_from_rpc(
+    request: &SimulationRequest,
+    operations: &soroban_env_host::xdr::VecM<soroban_env_host::xdr::Operation, 100>,
+    snapshot_entries: &mut HashMap<LedgerKey, Rc<LedgerEntry>>,
+    logs: &mut Vec<String>,
+) {
+    if request.offline.unwrap_or(false) {
+        logs.push("Entry fetch skipped: offline mode requested".to_string());
+        return;
+    }
+
+    let fetcher = match &request.data_source {
+        simulator::data_source::DataSourceConfig::Rpc { url, timeout_ms, retry } => {
+            let mut source = simulator::data_source::RpcLedgerSource::new(url.clone());
+            if let Some(timeout_ms) = timeout_ms {
+                source.timeout_ms = *timeout_ms;
+            }
+            if let Some(retry) = retry {
+                source.policy = (*retry).into();
+            }
+            EntryFetcher::DataSource(Box::new(source))
+        }
+        simulator::data_source::DataSourceConfig::Inline => {
+            let Some(rpc_url) = request.rpc_url.as_deref() else {
+                return;
             };
+            EntryFetcher::LegacyRpc { rpc_url, timeout_ms: request.rpc_timeout_ms.unwrap_or(rpc::DEFAULT_TIMEOUT_MS) }
+        }
+    };
 
-            json!({
-                "type": "auth",
-                "contract": contract_id,
-                "address": address,
-                "event_type": event_type_str,
-            })
-            .to_string()
-        } else if topic_str.contains("set")
-            || topic_str.contains("write")
-            || topic_str.contains("storage")
-        {
-            json!({
-                "type": "storage_write",
-                "contract": contract_id,
-                "event_type": event_type_str,
-            })
-            .to_string()
-        } else if topic_str.contains("call") || topic_str.contains("invoke") {
-            if let ScVal::Symbol(sym) = first_topic {
-                json!({
-                    "type": "contract_call",
-                    "contract": contract_id,
-                    "function": sym.to_string(),
-                    "event_type": event_type_str,
-                })
-                .to_string()
-            } else {
-                json!({
-                    "type": "contract_call",
-                    "contract": contract_id,
-                    "event_type": event_type_str,
-                })
-                .to_string()
+    for op in operations.iter() {
+        let soroban_env_host::xdr::OperationBody::InvokeHostFunction(host_fn_op) = &op.body else {
+            continue;
+        };
+        let soroban_env_host::xdr::HostFunction::InvokeContract(invoke_args) = &host_fn_op.host_function
+        else {
+            continue;
+        };
+
+        let instance_key = LedgerKey::ContractData(soroban_env_host::xdr::LedgerKeyContractData {
+            contract: invoke_args.contract_address.clone(),
+            key: ScVal::LedgerKeyContractInstance,
+            durability: soroban_env_host::xdr::ContractDataDurability::Persistent,
+        });
+
+        if !snapshot_entries.contains_key(&instance_key) {
+            match fetcher.fetch(&instance_key) {
+                Ok(Some(entry)) => {
+                    logs.push(format!("Fetched contract instance entry for {:?}", invoke_args.contract_address));
+                    snapshot_entries.insert(instance_key.clone(), Rc::new(entry));
+                }
+                Ok(None) => {}
+                Err(e) => logs.push(format!("Fetch of contract instance failed: {}", e)),
+            }
+        }
+
+        let Some(instance_entry) = snapshot_entries.get(&instance_key) else {
+            continue;
+        };
+        let LedgerEntryData::ContractData(cd) = &instance_entry.data else {
+            continue;
+        };
+        let ScVal::ContractInstance(instance) = &cd.val else {
+            continue;
+        };
+        let soroban_env_host::xdr::ContractExecutable::Wasm(wasm_hash) = &instance.executable else {
+            continue;
+        };
+
+        let code_key = LedgerKey::ContractCode(soroban_env_host::xdr::LedgerKeyContractCode {
+            hash: wasm_hash.clone(),
+        });
+        if snapshot_entries.contains_key(&code_key) {
+            continue;
+        }
+        match fetcher.fetch(&code_key) {
+            Ok(Some(entry)) => {
+                logs.push(format!("Fetched contract Wasm code entry (hash {:?})", wasm_hash));
+                snapshot_entries.insert(code_key, Rc::new(entry));
+            }
+            Ok(None) => {}
+            Err(e) => logs.push(format!("Fetch of contract code failed: {}", e)),
+        }
+    }
+}
+
+// Fee rates, in stroops, mirroring the network's published resource-fee
+// config. These are approximations for local preflight sizing, not the
+// authoritative schedule validators charge against.
+const FEE_PER_10K_INSTRUCTIONS: u64 = 100;
+const FEE_PER_MEMORY_BYTE: u64 = 1;
+const FEE_PER_READ_BYTE: u64 = 1;
+const FEE_PER_WRITE_BYTE: u64 = 5;
+const FEE_PER_BANDWIDTH_BYTE: u64 = 1;
+
+// Reference CPU/memory ceilings used only to turn `resource_estimate`'s
+// absolute consumption into the usage percentages
+// `optimization_report_for` feeds to `OptimizationReport::from_usage`.
+// Approximations of the network's default per-invocation resource limits,
+// not a value read back from `Budget` (no limit-reading API is exposed
+// alongside the consumption getters this module already calls).
+const REFERENCE_CPU_INSTRUCTION_LIMIT: u64 = 100_000_000;
+const REFERENCE_MEMORY_BYTE_LIMIT: u64 = 41_943_040;
+
+/// Approximates a per-10k-instruction fee rate from `calibration`'s crypto-op
+/// costs: the average of its three fixed per-call costs, scaled the same way
+/// as [`FEE_PER_10K_INSTRUCTIONS`] -- consistent with this module's existing
+/// framing of its fee constants as local sizing approximations rather than
+/// the authoritative schedule.
+fn calibration_derived_fee_per_10k_instructions(calibration: &simulator::types::ResourceCalibration) -> u64 {
+    (calibration.sha256_fixed + calibration.keccak256_fixed + calibration.ed25519_fixed) / 3
+}
+
+/// Reads budget consumption and the footprint built up on `host` during the
+/// invocation, and derives a resource fee estimate from them plus
+/// `tx_size_bytes`. When `request.mock_base_fee`/`mock_gas_price` are both
+/// set, the fee is `mock_base_fee + mock_gas_price * cpu_instructions`
+/// instead of the usual decomposition, for callers pinning a fee to a fixed
+/// or negotiated rate; otherwise `request.resource_calibration`, when
+/// present, replaces [`FEE_PER_10K_INSTRUCTIONS`] with a calibrated rate via
+/// [`calibration_derived_fee_per_10k_instructions`].
+fn compute_resource_estimate(
+    host: &Host,
+    tx_size_bytes: u64,
+    request: &SimulationRequest,
+) -> Result<ResourceEstimate, HostError> {
+    let budget = host.budget_cloned();
+    let cpu_instructions = budget.get_cpu_insns_consumed()?;
+    let memory_bytes = budget.get_mem_bytes_consumed()?;
+
+    let storage = host.try_borrow_storage()?;
+    let mut read_entries = 0u32;
+    let mut write_entries = 0u32;
+    let mut read_bytes = 0u64;
+    let mut write_bytes = 0u64;
+
+    for (key, access_type) in storage.footprint.0.iter() {
+        let entry_bytes = storage
+            .map
+            .get(key)
+            .and_then(|entry| entry.as_ref())
+            .and_then(|(entry, _live_until)| entry.to_xdr(soroban_env_host::xdr::Limits::none()).ok())
+            .map_or(0, |bytes| bytes.len() as u64);
+
+        match access_type {
+            soroban_env_host::storage::AccessType::ReadOnly => {
+                read_entries += 1;
+                read_bytes += entry_bytes;
+            }
+            soroban_env_host::storage::AccessType::ReadWrite => {
+                write_entries += 1;
+                write_bytes += entry_bytes;
             }
-        } else {
-            json!({
-                "type": "other",
-                "contract": contract_id,
-                "event_type": event_type_str,
-            })
-            .to_string()
         }
+    }
+
+    let resource_fee = if let (Some(base_fee), Some(gas_price)) = (request.mock_base_fee, request.mock_gas_price) {
+        u64::from(base_fee) + gas_price * cpu_instructions
     } else {
-        json!({
-            "type": "other",
-            "contract": contract_id,
-            "event_type": event_type_str,
-        })
-        .to_string()
+        let fee_per_10k_instructions = request
+            .resource_calibration
+            .as_ref()
+            .map(calibration_derived_fee_per_10k_instructions)
+            .unwrap_or(FEE_PER_10K_INSTRUCTIONS);
+
+        (cpu_instructions / 10_000) * fee_per_10k_instructions
+            + memory_bytes * FEE_PER_MEMORY_BYTE
+            + read_bytes * FEE_PER_READ_BYTE
+            + write_bytes * FEE_PER_WRITE_BYTE
+            + tx_size_bytes * FEE_PER_BANDWIDTH_BYTE
     };
 
-    Ok(event_json)
+    Ok(ResourceEstimate {
+        cpu_instructions,
+        memory_bytes,
+        read_entries,
+        write_entries,
+        read_bytes,
+        write_bytes,
+        resource_fee,
+    })
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct StructuredError {
-    error_type: String,
-    message: String,
-    details: Option<String>,
+/// Turns `estimate`'s CPU/memory consumption into an [`OptimizationReport`]
+/// when `request.enable_optimization_advisor` is `true`; `None` otherwise, or
+/// when `estimate` itself is `None` (`compute_resource_estimate` failed).
+///
+/// [`OptimizationReport`]: simulator::gas_optimizer::OptimizationReport
+fn optimization_report_for(
+    request: &SimulationRequest,
+    estimate: Option<&ResourceEstimate>,
+) -> Option<simulator::gas_optimizer::OptimizationReport> {
+    if request.enable_optimization_advisor != Some(true) {
+        return None;
+    }
+    let estimate = estimate?;
+    let cpu_usage_percent = estimate.cpu_instructions as f64 / REFERENCE_CPU_INSTRUCTION_LIMIT as f64 * 100.0;
+    let memory_usage_percent = estimate.memory_bytes as f64 / REFERENCE_MEMORY_BYTE_LIMIT as f64 * 100.0;
+    Some(simulator::gas_optimizer::OptimizationReport::from_usage(cpu_usage_percent, memory_usage_percent))
 }
 
-fn main() {
-    // Read JSON from Stdin
-    let mut buffer = String::new();
-    if let Err(e) = io::stdin().read_to_string(&mut buffer) {
-        eprintln!("Failed to read stdin: {}", e);
-        return;
+/// Runs [`simulator::vm::validate`] against every invoked contract's Wasm
+/// code entry already present in `snapshot_entries` (after
+/// `augment_snapshot_from_rpc` has had a chance to fetch it), under
+/// [`simulator::vm::SorobanFeatures::strict`], and appends one log line per
+/// violation found.
+///
+/// This only guards against Wasm the host would reject outright as
+/// incompatible; it does not predict a trap raised by the invocation's
+/// actual arguments or stored state (e.g. a `call_indirect` signature
+/// mismatch, or a panic deep in contract logic) -- those still surface, if
+/// at all, as the usual `HostTrap`/`Panic` response once execution runs.
+/// Resolving a trap back to a Wasm offset or source location is not yet
+/// integrated (see `simulator::stack_trace::WasmStackTrace`), since nothing
+/// in this crate yet exposes the trapping instruction's offset out of
+/// `Host::invoke_function`'s `HostError`.
+fn validate_invoked_contracts(
+    operations: &soroban_env_host::xdr::VecM<soroban_env_host::xdr::Operation, 100>,
+    snapshot_entries: &HashMap<LedgerKey, Rc<LedgerEntry>>,
+    logs: &mut Vec<String>,
+) {
+    for op in operations.iter() {
+        let soroban_env_host::xdr::OperationBody::InvokeHostFunction(host_fn_op) = &op.body else {
+            continue;
+        };
+        let soroban_env_host::xdr::HostFunction::InvokeContract(invoke_args) = &host_fn_op.host_function else {
+            continue;
+        };
+
+        let instance_key = LedgerKey::ContractData(soroban_env_host::xdr::LedgerKeyContractData {
+            contract: invoke_args.contract_address.clone(),
+            key: ScVal::LedgerKeyContractInstance,
+            durability: soroban_env_host::xdr::ContractDataDurability::Persistent,
+        });
+        let Some(instance_entry) = snapshot_entries.get(&instance_key) else { continue };
+        let LedgerEntryData::ContractData(cd) = &instance_entry.data else { continue };
+        let ScVal::ContractInstance(instance) = &cd.val else { continue };
+        let soroban_env_host::xdr::ContractExecutable::Wasm(wasm_hash) = &instance.executable else { continue };
+
+        let code_key =
+            LedgerKey::ContractCode(soroban_env_host::xdr::LedgerKeyContractCode { hash: wasm_hash.clone() });
+        let Some(code_entry) = snapshot_entries.get(&code_key) else { continue };
+        let LedgerEntryData::ContractCode(code) = &code_entry.data else { continue };
+
+        if let Err(violations) =
+            simulator::vm::validate(&code.code.to_vec(), &simulator::vm::SorobanFeatures::strict())
+        {
+            for violation in violations {
+                logs.push(format!("Wasm compatibility pre-flight: {violation}"));
+            }
+        }
     }
+}
 
-    // Parse Request
-    let request: SimulationRequest = match serde_json::from_str(&buffer) {
-        Ok(req) => req,
-        Err(e) => {
-            let res = SimulationResponse {
-                status: "error".to_string(),
-                error: Some(format!("Invalid JSON: {}", e)),
-                events: vec![],
-                categorized_events: vec![],
-                logs: vec![],
-            };
-            println!("{}", serde_json::to_string(&res).unwrap());
-            return;
+/// Converts an `ScVal` into a structured `serde_json::Value` instead of the
+/// `{:?}` debug string previously used for event topics/data and the
+/// invocation result.
+///
+/// Scalars map onto native JSON where it's lossless (`bool`, `u32`/`i32`,
+/// `String`/`Symbol`), and onto numeric strings where a JSON number would
+/// silently lose precision (`u64`/`i64`/`u128`/`i128`, which JavaScript's
+/// `Number` cannot round-trip past 2^53). `Bytes` is `0x`-prefixed hex via
+/// [`bytes_to_hex`].
+/// Anything that doesn't have an obvious JSON shape — notably `U256`/`I256`,
+/// and any variant added to the XDR after this was written — falls back to
+/// [`fallback_raw_xdr`] rather than guessing at a representation.
+fn scval_to_json(val: &ScVal, depth: usize) -> serde_json::Value {
+    if depth > SCVAL_JSON_DEPTH_LIMIT {
+        return fallback_raw_xdr(val);
+    }
+
+    match val {
+        ScVal::Bool(b) => json!(b),
+        ScVal::Void => serde_json::Value::Null,
+        ScVal::U32(n) => json!(n),
+        ScVal::I32(n) => json!(n),
+        ScVal::U64(n) => json!(n.to_string()),
+        ScVal::I64(n) => json!(n.to_string()),
+        ScVal::Timepoint(tp) => json!(tp.0.to_string()),
+        ScVal::Duration(d) => json!(d.0.to_string()),
+        ScVal::U128(parts) => {
+            json!(((u128::from(parts.hi) << 64) | u128::from(parts.lo)).to_string())
         }
-    };
+        ScVal::I128(parts) => {
+            json!((((i128::from(parts.hi)) << 64) | i128::from(parts.lo)).to_string())
+        }
+        ScVal::Bytes(bytes) => json!(bytes_to_hex(bytes)),
+        ScVal::String(s) => json!(s.to_string()),
+        ScVal::Symbol(sym) => json!(sym.to_string()),
+        ScVal::Vec(Some(vec)) => {
+            serde_json::Value::Array(vec.iter().map(|v| scval_to_json(v, depth + 1)).collect())
+        }
+        ScVal::Vec(None) => serde_json::Value::Array(vec![]),
+        ScVal::Map(Some(map)) => serde_json::Value::Object(
+            map.iter()
+                .map(|entry| {
+                    let key = match scval_to_json(&entry.key, depth + 1) {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (key, scval_to_json(&entry.val, depth + 1))
+                })
+                .collect(),
+        ),
+        ScVal::Map(None) => serde_json::Value::Object(serde_json::Map::new()),
+        ScVal::Address(addr) => json!(address_to_strkey(addr)),
+        _ => fallback_raw_xdr(val),
+    }
+}
+
+/// Encodes `bytes` as a `0x`-prefixed lowercase hex string.
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("0x");
+    for b in bytes {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
 
+/// Renders an `ScAddress` as its `G.../C...` strkey.
+fn address_to_strkey(addr: &ScAddress) -> String {
+    match addr {
+        ScAddress::Account(account_id) => {
+            let soroban_env_host::xdr::PublicKey::PublicKeyTypeEd25519(key) = &account_id.0;
+            stellar_strkey::ed25519::PublicKey(key.0).to_string()
+        }
+        ScAddress::Contract(hash) => stellar_strkey::Contract(hash.0).to_string(),
+    }
+}
+
+/// Last-resort representation for an `ScVal` that doesn't have a clean JSON
+/// shape: the raw XDR, base64-encoded, so no information is lost even though
+/// it isn't structured.
+fn fallback_raw_xdr(val: &ScVal) -> serde_json::Value {
+    match val.to_xdr(soroban_env_host::xdr::Limits::none()) {
+        Ok(bytes) => json!({ "raw_xdr": base64::engine::general_purpose::STANDARD.encode(bytes) }),
+        Err(e) => json!({ "raw_xdr": null, "encode_error": format!("{:?}", e) }),
+    }
+}
+
+/// Loads the manifest named by `$ERST_MANIFEST_PATH`, if set. A missing
+/// env var, unreadable file, or malformed JSON are all treated the same as
+/// "no manifest" -- resolution simply leaves `rpc_url` as the request
+/// supplied it -- rather than failing the simulation over a sizing aid.
+fn load_environment_manifest() -> Option<Manifest> {
+    let path = std::env::var_os("ERST_MANIFEST_PATH").filter(|v| !v.is_empty())?;
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Entry point used by both the one-shot stdin mode and `--serve` mode.
+/// Runs `request` against a fresh `Host` end to end and returns the
+/// response instead of printing it, so callers handling many requests
+/// (one per socket line, in `server::run`) never share `Host` state
+/// between them.
+pub(crate) fn simulate(mut request: SimulationRequest) -> SimulationResponse {
+    if let Some(name) = request.environment.as_deref() {
+        if let Some(manifest) = load_environment_manifest() {
+            let config = manifest.resolve(name);
+            request.rpc_url = request.rpc_url.take().or(config.rpc_url);
+            request.mock_base_fee = request.mock_base_fee.or(config.mock_base_fee);
+            request.mock_gas_price = request.mock_gas_price.or(config.mock_gas_price);
+            request.resource_calibration = request.resource_calibration.take().or(config.resource_calibration);
+            request.enable_optimization_advisor =
+                request.enable_optimization_advisor.or(config.enable_optimization_advisor);
+            request.profile = request.profile.or(config.profile);
+        }
+    }
     // Decode Envelope XDR
-    let envelope = match base64::engine::general_purpose::STANDARD.decode(&request.envelope_xdr) {
-        Ok(bytes) => match soroban_env_host::xdr::TransactionEnvelope::from_xdr(
-            bytes,
-            soroban_env_host::xdr::Limits::none(),
-        ) {
-            Ok(env) => env,
-            Err(e) => {
-                return send_error(format!("Failed to parse Envelope XDR: {}", e));
-            }
-        },
+    let envelope_bytes = match base64::engine::general_purpose::STANDARD.decode(&request.envelope_xdr) {
+        Ok(bytes) => bytes,
         Err(e) => {
-            return send_error(format!("Failed to decode Envelope Base64: {}", e));
+            return error_response(SimError::Base64Decode { field: "envelope_xdr", source: e });
+        }
+    };
+    let tx_size_bytes = envelope_bytes.len() as u64;
+    let envelope = match soroban_env_host::xdr::TransactionEnvelope::from_xdr(
+        envelope_bytes,
+        soroban_env_host::xdr::Limits::none(),
+    ) {
+        Ok(env) => env,
+        Err(e) => {
+            return error_response(SimError::XdrParse { kind: "TransactionEnvelope", source: e });
         }
     };
 
@@ -177,7 +649,7 @@ fn main() {
             ) {
                 Ok(meta) => Some(meta),
                 Err(e) => {
-                    return send_error(format!("Failed to parse ResultMeta XDR: {}", e));
+                    return error_response(SimError::XdrParse { kind: "TransactionResultMeta", source: e });
                 }
             },
             Err(e) => {
@@ -187,45 +659,35 @@ fn main() {
         }
     };
 
-    // Initialize Host
-    let host = soroban_env_host::Host::default();
-    host.set_diagnostic_level(soroban_env_host::DiagnosticLevel::Debug)
-        .unwrap();
-
-    // Populate Host Storage
+    // Decode the caller-supplied ledger snapshot into the in-memory source
+    // Storage reads against.
+    let mut snapshot_entries: HashMap<LedgerKey, Rc<LedgerEntry>> = HashMap::new();
     if let Some(entries) = &request.ledger_entries {
         for (key_xdr, entry_xdr) in entries {
-            // Decode Key
             let key = match base64::engine::general_purpose::STANDARD.decode(key_xdr) {
-                Ok(b) => match soroban_env_host::xdr::LedgerKey::from_xdr(
-                    b,
-                    soroban_env_host::xdr::Limits::none(),
-                ) {
+                Ok(b) => match LedgerKey::from_xdr(b, soroban_env_host::xdr::Limits::none()) {
                     Ok(k) => k,
-                    Err(e) => return send_error(format!("Failed to parse LedgerKey XDR: {}", e)),
+                    Err(e) => return error_response(SimError::XdrParse { kind: "LedgerKey", source: e }),
                 },
-                Err(e) => return send_error(format!("Failed to decode LedgerKey Base64: {}", e)),
+                Err(e) => {
+                    return error_response(SimError::Base64Decode { field: "ledger_entries.key", source: e })
+                }
             };
 
-            // Decode Entry
             let entry = match base64::engine::general_purpose::STANDARD.decode(entry_xdr) {
-                Ok(b) => match soroban_env_host::xdr::LedgerEntry::from_xdr(
-                    b,
-                    soroban_env_host::xdr::Limits::none(),
-                ) {
+                Ok(b) => match LedgerEntry::from_xdr(b, soroban_env_host::xdr::Limits::none()) {
                     Ok(e) => e,
-                    Err(e) => return send_error(format!("Failed to parse LedgerEntry XDR: {}", e)),
+                    Err(e) => return error_response(SimError::XdrParse { kind: "LedgerEntry", source: e }),
                 },
-                Err(e) => return send_error(format!("Failed to decode LedgerEntry Base64: {}", e)),
+                Err(e) => {
+                    return error_response(SimError::Base64Decode { field: "ledger_entries.value", source: e })
+                }
             };
 
-            // TODO: Inject into host storage.
-            // For MVP, we verify we can parse them.
-            eprintln!("Parsed Ledger Entry: Key={:?}, Entry={:?}", key, entry);
+            snapshot_entries.insert(key, Rc::new(entry));
         }
     }
-
-    let mut invocation_logs = vec![];
+    let loaded_entries_count = snapshot_entries.len();
 
     // Extract Operations from Envelope
     let operations = match &envelope {
@@ -236,6 +698,47 @@ fn main() {
         },
     };
 
+    let mut rpc_logs = vec![];
+    augment_snapshot_from_rpc(&request, operations, &mut snapshot_entries, &mut rpc_logs);
+
+    let mut entry_summaries: Vec<String> = snapshot_entries
+        .values()
+        .filter_map(|entry| match &entry.data {
+            LedgerEntryData::ContractData(cd) => Some(format!(
+                "contract_data key={} val={}",
+                scval_to_json(&cd.key, 0),
+                scval_to_json(&cd.val, 0)
+            )),
+            _ => None,
+        })
+        .collect();
+    entry_summaries.sort();
+
+    let mut preflight_logs = vec![];
+    validate_invoked_contracts(operations, &snapshot_entries, &mut preflight_logs);
+
+    // Initialize Host with a recording-footprint Storage backed by the
+    // decoded snapshot, so reads the contract performs are served from the
+    // entries the caller supplied rather than failing with "not found".
+    let storage = Storage::with_recording_footprint(Rc::new(InMemorySnapshot { entries: snapshot_entries }));
+    let host = Host::with_storage_and_budget(storage, Budget::default());
+    host.set_diagnostic_level(soroban_env_host::DiagnosticLevel::Debug)
+        .unwrap();
+    if let Err(e) = host.set_ledger_info(build_ledger_info(&request)) {
+        return error_response(SimError::StorageInjection(format!(
+            "failed to install ledger info: {:?}",
+            e
+        )));
+    }
+
+    let mut invocation_logs = vec![format!("Loaded {} ledger entries", loaded_entries_count)];
+    invocation_logs.extend(rpc_logs);
+    invocation_logs.extend(entry_summaries);
+    invocation_logs.extend(preflight_logs);
+    if request.profile.unwrap_or(false) {
+        invocation_logs.push("Profiling requested but not implemented in this build".to_string());
+    }
+
     // Iterate and find InvokeHostFunction
     // Wrap the contract invocation in panic protection
     let invocation_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
@@ -243,10 +746,18 @@ fn main() {
     }));
 
     match invocation_result {
-        Ok(Ok(execution_logs)) => {
+        Ok(Ok((execution_logs, result))) => {
             // Successful execution
             invocation_logs.extend(execution_logs);
 
+            let result_xdr = result.as_ref().and_then(|scval| {
+                scval
+                    .to_xdr(soroban_env_host::xdr::Limits::none())
+                    .ok()
+                    .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+            });
+            let result_json = result.as_ref().map(|scval| scval_to_json(scval, 0));
+
             // Capture Diagnostic Events
             let events = match host.get_events() {
                 Ok(evs) => evs
@@ -256,36 +767,44 @@ fn main() {
                     .collect::<Vec<String>>(),
                 Err(e) => vec![format!("Failed to retrieve events: {:?}", e)],
             };
+            let event_query = request.events.clone().unwrap_or_default();
+            let categorized_events = match host.get_events() {
+                Ok(evs) => categorize_events(&evs, &event_query),
+                Err(_) => serde_json::Value::Array(vec![]),
+            };
+            let resource_estimate = compute_resource_estimate(&host, tx_size_bytes, &request).ok();
+            let optimization_report = optimization_report_for(&request, resource_estimate.as_ref());
 
             // Success Response
-            let response = SimulationResponse {
+            SimulationResponse {
                 status: "success".to_string(),
                 error: None,
                 events,
+                categorized_events,
                 logs: invocation_logs,
-            };
-
-            println!("{}", serde_json::to_string(&response).unwrap());
+                result_xdr,
+                result: result_json,
+                resource_estimate,
+                optimization_report,
+            }
         }
         Ok(Err(host_error)) => {
             // Host error during execution (e.g., contract trap, validation failure)
-            let structured_error = StructuredError {
-                error_type: "HostError".to_string(),
-                message: format!("{:?}", host_error),
-                details: Some(format!(
-                    "Contract execution failed with host error: {:?}",
-                    host_error
-                )),
-            };
+            let sim_error = host_trap_from(&host_error);
+            let resource_estimate = compute_resource_estimate(&host, tx_size_bytes, &request).ok();
+            let optimization_report = optimization_report_for(&request, resource_estimate.as_ref());
 
-            let response = SimulationResponse {
+            SimulationResponse {
                 status: "error".to_string(),
-                error: Some(serde_json::to_string(&structured_error).unwrap()),
+                error: Some(sim_error.into_response_error()),
                 events: vec![],
+                categorized_events: serde_json::Value::Array(vec![]),
                 logs: invocation_logs,
-            };
-
-            println!("{}", serde_json::to_string(&response).unwrap());
+                result_xdr: None,
+                result: None,
+                resource_estimate,
+                optimization_report,
+            }
         }
         Err(panic_info) => {
             // Panic occurred during execution
@@ -297,35 +816,74 @@ fn main() {
                 "Unknown panic occurred".to_string()
             };
 
-            let structured_error = StructuredError {
-                error_type: "Panic".to_string(),
-                message: panic_message.clone(),
-                details: Some(format!(
-                    "Contract execution panicked. This typically indicates a critical error in the contract or host. Panic message: {}",
-                    panic_message
-                )),
-            };
-
             invocation_logs.push(format!("PANIC: {}", panic_message));
 
-            let response = SimulationResponse {
+            let resource_estimate = compute_resource_estimate(&host, tx_size_bytes, &request).ok();
+            let optimization_report = optimization_report_for(&request, resource_estimate.as_ref());
+
+            SimulationResponse {
                 status: "error".to_string(),
-                error: Some(serde_json::to_string(&structured_error).unwrap()),
+                error: Some(SimError::Panic(panic_message).into_response_error()),
                 events: vec![],
+                categorized_events: serde_json::Value::Array(vec![]),
                 logs: invocation_logs,
-            };
+                result_xdr: None,
+                result: None,
+                resource_estimate,
+                optimization_report,
+            }
+        }
+    }
+}
+
+/// Runs the stdin mode: read one `SimulationRequest` JSON document, print
+/// one `SimulationResponse` JSON document, exit. This stays the default
+/// entry point; `--serve` is opt-in for callers that want to amortize
+/// process-spawn overhead across many simulations.
+fn run_stdin_mode() {
+    let mut buffer = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut buffer) {
+        eprintln!("Failed to read stdin: {}", e);
+        return;
+    }
 
-            println!("{}", serde_json::to_string(&response).unwrap());
+    let request: SimulationRequest = match serde_json::from_str(&buffer) {
+        Ok(req) => req,
+        Err(e) => {
+            return send_error(SimError::InvalidJson(e.to_string()));
         }
+    };
+
+    let response = simulate(request);
+    println!("{}", serde_json::to_string(&response).unwrap());
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("--serve") => {
+            let addr = args.next().unwrap_or_else(|| "127.0.0.1:8787".to_string());
+            if let Err(e) = server::run(&addr) {
+                eprintln!("simulator: server exited with error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(other) => {
+            eprintln!("simulator: unrecognized argument '{}'. Reading from stdin instead; pass '--serve [addr]' to listen on a socket instead.", other);
+            run_stdin_mode();
+        }
+        None => run_stdin_mode(),
     }
 }
 
-/// Execute operations and handle host errors
+/// Execute operations and handle host errors. Returns the accumulated log
+/// lines plus the `ScVal` the last `InvokeContract` call returned, if any.
 fn execute_operations(
-    _host: &soroban_env_host::Host,
+    host: &Host,
     operations: &soroban_env_host::xdr::VecM<soroban_env_host::xdr::Operation, 100>,
-) -> Result<Vec<String>, soroban_env_host::HostError> {
+) -> Result<(Vec<String>, Option<ScVal>), HostError> {
     let mut logs = vec![];
+    let mut last_result = None;
 
     for op in operations.iter() {
         if let soroban_env_host::xdr::OperationBody::InvokeHostFunction(host_fn_op) = &op.body {
@@ -341,20 +899,14 @@ fn execute_operations(
                     logs.push(format!("Function: {:?}", func_name));
                     logs.push(format!("Args Count: {}", invoke_args_vec.len()));
 
-                    // In a full implementation, we'd do:
-                    // let res = host.invoke_function(...)?;
-                    // For now, this is a placeholder for actual contract invocation
-
-                    // Example of how to handle HostError propagation:
-                    // match host.invoke_function(...) {
-                    //     Ok(result) => {
-                    //         logs.push(format!("Invocation successful: {:?}", result));
-                    //     }
-                    //     Err(e) => {
-                    //         // Propagate HostError up to be caught by the outer handler
-                    //         return Err(e);
-                    //     }
-                    // }
+                    // NOTE: authorization (SorobanAuthorizationEntry) is not
+                    // wired up yet, so this runs without enforcing `require_auth`
+                    // checks against the envelope's auth entries.
+                    let result = host.invoke_function(soroban_env_host::xdr::HostFunction::InvokeContract(
+                        invoke_args.clone(),
+                    ))?;
+                    logs.push(format!("Invocation successful: {:?}", result));
+                    last_result = Some(result);
                 }
                 _ => {
                     logs.push("Skipping non-InvokeContract Host Function".to_string());
@@ -363,131 +915,123 @@ fn execute_operations(
         }
     }
 
-<<<<<<< HEAD
-    let events = match host.get_events() {
-        Ok(evs) => {
-            let mut categorized_events = Vec::new();
-
-            for host_event in evs.0.iter() {
-                let event_json = match categorize_event_for_analyzer(host_event) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        eprintln!("Warning: Failed to categorize event: {}", e);
-                        format!("{{\"type\":\"other\",\"raw\":\"{:?}\"}}", host_event)
-                    }
-                };
-                categorized_events.push(event_json);
-            }
+    Ok((logs, last_result))
+}
 
-            categorized_events
-        }
-        Err(e) => vec![format!(
-            "{{\"type\":\"error\",\"message\":\"Failed to retrieve events: {}\"}}",
-            e
-        )],
-    };
+/// Classifies a single event's type the same heuristic way the original
+/// debug-string-only version did: `System`/`Diagnostic` map directly, and
+/// `Contract` refines further by inspecting the first topic when it's a
+/// recognizable `Symbol`.
+fn classify_event_type(event_type: &soroban_env_host::xdr::ContractEventType, topics: &[ScVal]) -> &'static str {
+    use soroban_env_host::xdr::ContractEventType;
 
-    let categorized_events = match host.get_events() {
-        Ok(evs) => categorize_events(&evs),
-        Err(_) => vec![],
+    let first_symbol = match topics.first() {
+        Some(ScVal::Symbol(sym)) => Some(sym.to_string()),
+        _ => None,
     };
 
-    let response = SimulationResponse {
-        status: "success".to_string(),
-        error: None,
-        events,
-        categorized_events,
-        logs: {
-            let mut logs = vec![
-                format!("Host Initialized with Budget: {:?}", host.budget_cloned()),
-                format!("Loaded {} Ledger Entries", loaded_entries_count),
-            ];
-            logs.extend(invocation_logs);
-            logs
+    match event_type {
+        ContractEventType::Contract => match first_symbol.as_deref() {
+            Some(s) if s.contains("require_auth") => "require_auth",
+            Some(s) if s.contains("set") || s.contains("write") => "storage_write",
+            _ => "contract",
         },
-    };
-
-    println!("{}", serde_json::to_string(&response).unwrap());
-=======
-    Ok(logs)
->>>>>>> upstream/main
+        ContractEventType::System => "system",
+        ContractEventType::Diagnostic => match first_symbol.as_deref() {
+            Some(s) if s.contains("fn_call") => "invocation",
+            Some(s) if s.contains("fn_return") => "return",
+            _ => "diagnostic",
+        },
+    }
 }
 
-fn categorize_events(events: &Events) -> Vec<CategorizedEvent> {
-    use soroban_env_host::xdr::{ContractEventBody, ContractEventType, ScVal};
+/// Filters and shapes `events` per `query`, emitting topics/data through
+/// [`scval_to_json`] instead of `{:?}` debug strings. Each surviving event
+/// carries its `index` within this invocation's event list (not a
+/// network-wide ledger sequence — one `simulate` call covers one ledger's
+/// worth of events) so a caller correlating events across a batch of
+/// simulated transactions can tell them apart.
+fn categorize_events(events: &Events, query: &EventQuery) -> serde_json::Value {
+    use soroban_env_host::xdr::ContractEventBody;
 
-    events
+    let mut rows: Vec<serde_json::Value> = events
         .0
         .iter()
-        .filter_map(|event| {
-            // Access body to get topics and data
+        .enumerate()
+        .filter(|(_, event)| !event.failed_call)
+        .filter_map(|(index, event)| {
             let (topics, data_val) = match &event.event.body {
                 ContractEventBody::V0(v0) => (&v0.topics, &v0.data),
             };
 
-            if !event.failed_call {
-                let event_type = match &event.event.type_ {
-                    ContractEventType::Contract => {
-                        if let Some(topic) = topics.get(0) {
-                            if let ScVal::Symbol(sym) = topic {
-                                match sym.to_string().as_str() {
-                                    s if s.contains("require_auth") => "require_auth",
-                                    s if s.contains("set") || s.contains("write") => {
-                                        "storage_write"
-                                    }
-                                    _ => "contract",
-                                }
-                            } else {
-                                "contract"
-                            }
-                        } else {
-                            "contract"
-                        }
-                    }
-                    ContractEventType::System => "system",
-                    ContractEventType::Diagnostic => {
-                        if let Some(topic) = topics.get(0) {
-                            if let ScVal::Symbol(sym) = topic {
-                                match sym.to_string().as_str() {
-                                    s if s.contains("fn_call") => "invocation",
-                                    s if s.contains("fn_return") => "return",
-                                    _ => "diagnostic",
-                                }
-                            } else {
-                                "diagnostic"
-                            }
-                        } else {
-                            "diagnostic"
-                        }
-                    }
-                };
+            let event_type = classify_event_type(&event.event.type_, topics);
+            if let Some(want) = query.event_type.as_deref() {
+                if !event_type.eq_ignore_ascii_case(want) {
+                    return None;
+                }
+            }
 
-                Some(CategorizedEvent {
-                    event_type: event_type.to_string(),
-                    contract_id: event
-                        .event
-                        .contract_id
-                        .as_ref()
-                        .map(|id| format!("{:?}", id)),
-                    topics: topics.iter().map(|t| format!("{:?}", t)).collect(),
-                    data: format!("{:?}", data_val),
-                })
-            } else {
-                None
+            let contract_id = event
+                .event
+                .contract_id
+                .as_ref()
+                .map(|id| stellar_strkey::Contract(id.0).to_string());
+            if let Some(want) = query.contract_id.as_deref() {
+                if contract_id.as_deref() != Some(want) {
+                    return None;
+                }
             }
+
+            if let Some(prefix) = query.topic_prefix.as_deref() {
+                let starts_with_prefix = matches!(
+                    topics.first(),
+                    Some(ScVal::Symbol(sym)) if sym.to_string().starts_with(prefix)
+                );
+                if !starts_with_prefix {
+                    return None;
+                }
+            }
+
+            let topics_json: Vec<serde_json::Value> = topics.iter().map(|t| scval_to_json(t, 0)).collect();
+            let data_json = scval_to_json(data_val, 0);
+
+            Some(match query.format {
+                EventFormat::Pretty => json!({
+                    "index": index,
+                    "event_type": event_type,
+                    "contract_id": contract_id,
+                    "topics": topics_json,
+                    "data": data_json,
+                }),
+                EventFormat::Compact => json!([index, event_type, contract_id, topics_json, data_json]),
+            })
         })
-        .collect()
+        .collect();
+
+    if let Some(count) = query.count {
+        rows.truncate(count);
+    }
+
+    serde_json::Value::Array(rows)
 }
 
-fn send_error(msg: String) {
-    let res = SimulationResponse {
+/// Builds an error [`SimulationResponse`] without printing it, so both
+/// `run_stdin_mode` (which prints it once) and `server::handle_connection`
+/// (which writes it back over a socket) can share the same construction.
+pub(crate) fn error_response(err: SimError) -> SimulationResponse {
+    SimulationResponse {
         status: "error".to_string(),
-        error: Some(msg),
+        error: Some(err.into_response_error()),
         events: vec![],
-        categorized_events: vec![],
+        categorized_events: serde_json::Value::Array(vec![]),
         logs: vec![],
-    };
-    println!("{}", serde_json::to_string(&res).unwrap());
+        result_xdr: None,
+        result: None,
+        resource_estimate: None,
+        optimization_report: None,
+    }
 }
 
-mod test;
+fn send_error(err: SimError) {
+    println!("{}", serde_json::to_string(&error_response(err)).unwrap());
+}
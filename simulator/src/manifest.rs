@@ -0,0 +1,146 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! A checked-in config file defining named environments (`testnet`,
+//! `mainnet`, `local`, ...), each carrying its own [`ResourceCalibration`],
+//! mock-fee defaults, RPC endpoint, and advisor/profiling toggles, so a
+//! caller doesn't have to re-specify them on every [`crate::types::SimulationRequest`].
+//!
+//! A request names its environment via `SimulationRequest::environment`;
+//! [`SimulationRequest::apply_environment`] then fills in whichever of its
+//! own fields were left `None` from that environment's resolved
+//! [`EnvironmentConfig`] (the named environment's section merged over
+//! `Manifest::defaults`, with the environment's own value winning).
+
+use crate::types::ResourceCalibration;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Top-level shape of the manifest file (TOML or JSON; `serde` doesn't care
+/// which as long as the caller picks a matching deserializer).
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct Manifest {
+    /// Applied to every environment before its own section's overrides.
+    #[serde(default)]
+    pub defaults: EnvironmentConfig,
+    #[serde(default)]
+    pub environments: HashMap<String, EnvironmentConfig>,
+}
+
+impl Manifest {
+    /// Resolves `name`'s section merged over [`Manifest::defaults`] (the
+    /// environment's own value wins wherever both set a field). An unknown
+    /// environment name resolves to `defaults` alone.
+    pub fn resolve(&self, name: &str) -> EnvironmentConfig {
+        match self.environments.get(name) {
+            Some(env) => self.defaults.clone().merged_with(env.clone()),
+            None => self.defaults.clone(),
+        }
+    }
+}
+
+/// One environment's worth of defaults; every field is optional so a
+/// section only needs to mention what it overrides.
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct EnvironmentConfig {
+    pub resource_calibration: Option<ResourceCalibration>,
+    pub mock_base_fee: Option<u32>,
+    pub mock_gas_price: Option<u64>,
+    pub rpc_url: Option<String>,
+    pub enable_optimization_advisor: Option<bool>,
+    pub profile: Option<bool>,
+}
+
+impl EnvironmentConfig {
+    /// Merges `other` over `self`, with `other`'s value winning wherever
+    /// both set a field.
+    fn merged_with(self, other: Self) -> Self {
+        Self {
+            resource_calibration: other.resource_calibration.or(self.resource_calibration),
+            mock_base_fee: other.mock_base_fee.or(self.mock_base_fee),
+            mock_gas_price: other.mock_gas_price.or(self.mock_gas_price),
+            rpc_url: other.rpc_url.or(self.rpc_url),
+            enable_optimization_advisor: other.enable_optimization_advisor.or(self.enable_optimization_advisor),
+            profile: other.profile.or(self.profile),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calibration(sha256_fixed: u64) -> ResourceCalibration {
+        ResourceCalibration {
+            sha256_fixed,
+            sha256_per_byte: 1,
+            keccak256_fixed: 1,
+            keccak256_per_byte: 1,
+            ed25519_fixed: 1,
+        }
+    }
+
+    #[test]
+    fn test_resolve_merges_environment_over_defaults() {
+        let manifest = Manifest {
+            defaults: EnvironmentConfig {
+                mock_base_fee: Some(100),
+                rpc_url: Some("https://default.example.com".to_string()),
+                ..Default::default()
+            },
+            environments: HashMap::from([(
+                "testnet".to_string(),
+                EnvironmentConfig { rpc_url: Some("https://testnet.example.com".to_string()), ..Default::default() },
+            )]),
+        };
+
+        let resolved = manifest.resolve("testnet");
+        assert_eq!(resolved.mock_base_fee, Some(100), "unset fields fall back to defaults");
+        assert_eq!(resolved.rpc_url, Some("https://testnet.example.com".to_string()), "set fields override defaults");
+    }
+
+    #[test]
+    fn test_resolve_unknown_environment_falls_back_to_defaults_alone() {
+        let manifest = Manifest {
+            defaults: EnvironmentConfig { mock_base_fee: Some(100), ..Default::default() },
+            environments: HashMap::new(),
+        };
+        let resolved = manifest.resolve("does-not-exist");
+        assert_eq!(resolved.mock_base_fee, Some(100));
+    }
+
+    #[test]
+    fn test_resolve_carries_resource_calibration() {
+        let manifest = Manifest {
+            defaults: EnvironmentConfig::default(),
+            environments: HashMap::from([(
+                "mainnet".to_string(),
+                EnvironmentConfig { resource_calibration: Some(calibration(42)), ..Default::default() },
+            )]),
+        };
+        let resolved = manifest.resolve("mainnet");
+        assert_eq!(resolved.resource_calibration.unwrap().sha256_fixed, 42);
+    }
+
+    #[test]
+    fn test_manifest_deserializes_from_json_with_partial_sections() {
+        let json = serde_json::json!({
+            "defaults": { "mock_base_fee": 100 },
+            "environments": {
+                "local": { "rpc_url": "http://localhost:8000/soroban/rpc", "profile": true }
+            }
+        });
+        let manifest: Manifest = serde_json::from_value(json).unwrap();
+        let resolved = manifest.resolve("local");
+        assert_eq!(resolved.mock_base_fee, Some(100));
+        assert_eq!(resolved.rpc_url, Some("http://localhost:8000/soroban/rpc".to_string()));
+        assert_eq!(resolved.profile, Some(true));
+    }
+
+    #[test]
+    fn test_manifest_missing_sections_default_to_empty() {
+        let manifest: Manifest = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(manifest.environments.is_empty());
+        assert_eq!(manifest.defaults.mock_base_fee, None);
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! A minimal client for the Soroban RPC `getLedgerEntries` method: encode a
+//! batch of [`LedgerKey`]s, POST them as one JSON-RPC request, decode the
+//! returned entries. Used by [`crate::augment_snapshot_from_rpc`] to
+//! backfill ledger entries a [`crate::SimulationRequest`] didn't supply
+//! directly.
+
+use base64::Engine as _;
+use soroban_env_host::xdr::{LedgerEntry, LedgerKey, Limits, ReadXdr, WriteXdr};
+use std::time::Duration;
+
+/// Default round-trip timeout, chosen to fail a simulation fast rather than
+/// hang indefinitely on a slow or unreachable RPC endpoint.
+pub(crate) const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+
+#[derive(Debug)]
+pub(crate) enum RpcError {
+    /// The HTTP request itself failed (network error, timeout, non-2xx).
+    Request(String),
+    /// A response was received but didn't have the shape `getLedgerEntries`
+    /// promises, or carried a JSON-RPC `error` object.
+    Response(String),
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcError::Request(e) => write!(f, "RPC request failed: {}", e),
+            RpcError::Response(e) => write!(f, "RPC response invalid: {}", e),
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct GetLedgerEntriesResponse {
+    result: Option<GetLedgerEntriesResult>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(serde::Deserialize)]
+struct JsonRpcError {
+    message: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GetLedgerEntriesResult {
+    entries: Option<Vec<LedgerEntryResult>>,
+}
+
+#[derive(serde::Deserialize)]
+struct LedgerEntryResult {
+    xdr: String,
+}
+
+/// Fetches `keys` from `rpc_url` via `getLedgerEntries`. Keys the endpoint
+/// has no entry for (deleted, or never existed) are simply absent from the
+/// returned list rather than being an error.
+pub(crate) fn fetch_ledger_entries(
+    rpc_url: &str,
+    keys: &[LedgerKey],
+    timeout_ms: u64,
+) -> Result<Vec<LedgerEntry>, RpcError> {
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let key_xdrs: Vec<String> = keys
+        .iter()
+        .map(|key| {
+            key.to_xdr(Limits::none())
+                .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+                .map_err(|e| RpcError::Request(format!("failed to encode LedgerKey: {:?}", e)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getLedgerEntries",
+        "params": { "keys": key_xdrs },
+    });
+
+    let response: GetLedgerEntriesResponse = ureq::post(rpc_url)
+        .timeout(Duration::from_millis(timeout_ms))
+        .send_json(body)
+        .map_err(|e| RpcError::Request(e.to_string()))?
+        .into_json()
+        .map_err(|e| RpcError::Response(e.to_string()))?;
+
+    if let Some(err) = response.error {
+        return Err(RpcError::Response(err.message));
+    }
+
+    response
+        .result
+        .and_then(|r| r.entries)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(&entry.xdr)
+                .map_err(|e| RpcError::Response(format!("invalid entry XDR base64: {}", e)))?;
+            LedgerEntry::from_xdr(bytes, Limits::none())
+                .map_err(|e| RpcError::Response(format!("invalid entry XDR: {:?}", e)))
+        })
+        .collect()
+}
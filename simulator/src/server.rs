@@ -0,0 +1,169 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! `--serve` mode: a long-lived TCP listener that amortizes process-spawn
+//! overhead across many [`crate::SimulationRequest`]s per connection,
+//! instead of the default stdin mode's one-request-per-process model.
+//!
+//! Framing is newline-delimited JSON: one request per line in, one
+//! response per line out. Each request still runs through [`crate::simulate`]
+//! with its own fresh `Host`, so nothing carries over between requests,
+//! even ones sharing a connection.
+//!
+//! A [`Worker`]'s job loop runs every connection inside `panic::catch_unwind`
+//! and recovers a poisoned job queue rather than propagating either into the
+//! worker thread, so one connection's panic can't permanently shrink
+//! [`WorkerPool`]'s capacity the way an unguarded thread death would.
+
+use crate::error::SimError;
+use crate::{error_response, simulate, SimulationRequest};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Worker threads kept alive for the server's lifetime. Bounds how many
+/// connections are serviced concurrently so a burst of clients can't
+/// exhaust memory by each triggering an unbounded `thread::spawn`.
+const WORKER_COUNT: usize = 8;
+
+/// Binds `addr` (e.g. `"127.0.0.1:8787"`) and services connections with a
+/// bounded worker pool until the process is killed.
+pub(crate) fn run(addr: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("simulator: serving on {}", addr);
+    let pool = WorkerPool::new(WORKER_COUNT);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => pool.execute(move || handle_connection(stream)),
+            Err(e) => eprintln!("simulator: failed to accept connection: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads newline-delimited `SimulationRequest` JSON from `stream` until EOF,
+/// running each through [`simulate`] and writing back the corresponding
+/// newline-delimited `SimulationResponse` JSON.
+fn handle_connection(stream: TcpStream) {
+    let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_else(|_| "<unknown>".to_string());
+
+    let Ok(cloned) = stream.try_clone() else {
+        eprintln!("simulator: failed to clone stream for {}", peer);
+        return;
+    };
+    let reader = BufReader::new(cloned);
+    let mut writer = stream;
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) if l.trim().is_empty() => continue,
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("simulator: read error from {}: {}", peer, e);
+                return;
+            }
+        };
+
+        let response = match serde_json::from_str::<SimulationRequest>(&line) {
+            Ok(request) => simulate(request),
+            Err(e) => error_response(SimError::InvalidJson(e.to_string())),
+        };
+
+        let Ok(mut serialized) = serde_json::to_string(&response) else {
+            eprintln!("simulator: failed to serialize response for {}", peer);
+            return;
+        };
+        serialized.push('\n');
+
+        if let Err(e) = writer.write_all(serialized.as_bytes()) {
+            eprintln!("simulator: write error to {}: {}", peer, e);
+            return;
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// A fixed-size pool of worker threads pulling jobs off a shared queue, so
+/// the number of connections serviced at once is bounded regardless of how
+/// many clients connect.
+struct WorkerPool {
+    workers: Vec<Worker>,
+    sender: Option<mpsc::Sender<Job>>,
+}
+
+impl WorkerPool {
+    fn new(size: usize) -> Self {
+        assert!(size > 0, "worker pool size must be nonzero");
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..size).map(|id| Worker::new(id, Arc::clone(&receiver))).collect();
+
+        Self { workers, sender: Some(sender) }
+    }
+
+    fn execute<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        // `sender` is only `None` after `drop`, which can't run while `self`
+        // is still reachable here.
+        self.sender.as_ref().unwrap().send(Box::new(job)).expect("worker pool channel closed");
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        for worker in &mut self.workers {
+            if let Some(handle) = worker.thread.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+struct Worker {
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    /// Runs `receiver`'s job loop until the channel closes. Each job runs
+    /// inside `panic::catch_unwind` so a connection that panics logs and
+    /// moves on to the next job instead of unwinding out of the loop and
+    /// taking this worker thread down with it -- `WorkerPool` has no
+    /// respawn mechanism, so a dead worker would otherwise permanently
+    /// shrink its capacity by one. The queue mutex is recovered on poison
+    /// rather than unwrapped for the same reason: a lock poisoned by some
+    /// future fallible section shouldn't also take a worker down.
+    fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Self {
+        let thread = thread::Builder::new()
+            .name(format!("sim-worker-{}", id))
+            .spawn(move || loop {
+                let job = receiver.lock().unwrap_or_else(std::sync::PoisonError::into_inner).recv();
+                match job {
+                    Ok(job) => {
+                        if let Err(panic_info) = panic::catch_unwind(panic::AssertUnwindSafe(job)) {
+                            let message = if let Some(s) = panic_info.downcast_ref::<&str>() {
+                                (*s).to_string()
+                            } else if let Some(s) = panic_info.downcast_ref::<String>() {
+                                s.clone()
+                            } else {
+                                "unknown panic".to_string()
+                            };
+                            eprintln!("simulator: worker {} job panicked: {}", id, message);
+                        }
+                    }
+                    Err(_) => break,
+                }
+            })
+            .expect("failed to spawn worker thread");
+
+        Self { thread: Some(thread) }
+    }
+}
@@ -14,8 +14,9 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Inline OS-level advisory file locking using libc, which is a transitive
 // dependency of soroban-env-host. This avoids adding a new crate while still
@@ -80,6 +81,289 @@ mod flock {
 /// Default cache directory name
 pub const CACHE_DIR_NAME: &str = "sourcemaps";
 
+/// Why [`SourceMapCache::new`] or [`SourceMapCache::with_cache_dir`] couldn't
+/// resolve a usable cache directory. Distinguishes "there was nowhere to even
+/// try" ([`CacheDirError::NoHomeDirectory`]) from "a directory was tried and
+/// rejected" ([`CacheDirError::NotWritable`]), since only the latter points
+/// at a specific path worth surfacing in a diagnostic.
+#[derive(Debug)]
+pub enum CacheDirError {
+    /// No env var candidate was set and no home directory could be
+    /// determined, so even the `~/.erst/cache/sourcemaps` fallback had
+    /// nowhere to anchor.
+    NoHomeDirectory,
+    /// A candidate directory was resolved but isn't writable (permissions,
+    /// read-only filesystem, etc.).
+    NotWritable { path: PathBuf, source: std::io::Error },
+}
+
+impl std::fmt::Display for CacheDirError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheDirError::NoHomeDirectory => {
+                write!(f, "could not determine a home directory for the source map cache")
+            }
+            CacheDirError::NotWritable { path, source } => {
+                write!(f, "cache directory {:?} is not writable: {}", path, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CacheDirError {}
+
+/// On-disk cache file format: a fixed header, a sorted index of
+/// `(wasm_offset, blob_offset, blob_len)` tuples, then a packed region of
+/// per-location bincode blobs. Letting `get` mmap the file and binary-search
+/// the index means a single offset lookup no longer pays to deserialize
+/// every mapping in a large contract's table — see [`CacheFileHandle`]. The
+/// header also carries a SHA256 checksum over the index+blob region, so a
+/// truncated write or bit-rot that still happens to parse is still caught —
+/// see [`verify_checksum`].
+mod format {
+    use super::SourceLocation;
+    use sha2::{Digest, Sha256};
+    use std::collections::HashMap;
+
+    /// Distinguishes this layout from the plain whole-struct bincode files
+    /// written before this format existed, and from any future revision.
+    pub(super) const MAGIC: u32 = 0x534D_4331; // "SMC1"
+    pub(super) const VERSION: u32 = 2;
+    const CHECKSUM_SIZE: usize = 32;
+    pub(super) const HEADER_SIZE: usize = 24 + CHECKSUM_SIZE;
+    const INDEX_ENTRY_SIZE: usize = 16;
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    pub(super) struct Header {
+        pub(super) magic: u32,
+        pub(super) version: u32,
+        pub(super) has_symbols: u32,
+        pub(super) entry_count: u32,
+        pub(super) created_at: u64,
+        /// SHA256 of the index+blob region, checked by [`verify_checksum`].
+        pub(super) checksum: [u8; CHECKSUM_SIZE],
+    }
+
+    impl Header {
+        pub(super) fn to_bytes(self) -> [u8; HEADER_SIZE] {
+            let mut buf = [0u8; HEADER_SIZE];
+            buf[0..4].copy_from_slice(&self.magic.to_le_bytes());
+            buf[4..8].copy_from_slice(&self.version.to_le_bytes());
+            buf[8..12].copy_from_slice(&self.has_symbols.to_le_bytes());
+            buf[12..16].copy_from_slice(&self.entry_count.to_le_bytes());
+            buf[16..24].copy_from_slice(&self.created_at.to_le_bytes());
+            buf[24..24 + CHECKSUM_SIZE].copy_from_slice(&self.checksum);
+            buf
+        }
+
+        /// Returns `None` for anything shorter than a header, or whose
+        /// `magic`/`version` don't match — which includes pre-mmap-format
+        /// `.bin` files and files from the pre-checksum v1 layout, so they're
+        /// treated as a cache miss rather than mis-parsed.
+        pub(super) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+            if bytes.len() < HEADER_SIZE {
+                return None;
+            }
+            let magic = u32::from_le_bytes(bytes[0..4].try_into().ok()?);
+            let version = u32::from_le_bytes(bytes[4..8].try_into().ok()?);
+            if magic != MAGIC || version != VERSION {
+                return None;
+            }
+            let mut checksum = [0u8; CHECKSUM_SIZE];
+            checksum.copy_from_slice(&bytes[24..24 + CHECKSUM_SIZE]);
+            Some(Self {
+                magic,
+                version,
+                has_symbols: u32::from_le_bytes(bytes[8..12].try_into().ok()?),
+                entry_count: u32::from_le_bytes(bytes[12..16].try_into().ok()?),
+                created_at: u64::from_le_bytes(bytes[16..24].try_into().ok()?),
+                checksum,
+            })
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub(super) struct IndexEntry {
+        pub(super) wasm_offset: u64,
+        /// Absolute byte offset of this blob within the file.
+        pub(super) blob_offset: u32,
+        pub(super) blob_len: u32,
+    }
+
+    impl IndexEntry {
+        fn to_bytes(self) -> [u8; INDEX_ENTRY_SIZE] {
+            let mut buf = [0u8; INDEX_ENTRY_SIZE];
+            buf[0..8].copy_from_slice(&self.wasm_offset.to_le_bytes());
+            buf[8..12].copy_from_slice(&self.blob_offset.to_le_bytes());
+            buf[12..16].copy_from_slice(&self.blob_len.to_le_bytes());
+            buf
+        }
+
+        fn from_bytes(bytes: &[u8]) -> Option<Self> {
+            if bytes.len() < INDEX_ENTRY_SIZE {
+                return None;
+            }
+            Some(Self {
+                wasm_offset: u64::from_le_bytes(bytes[0..8].try_into().ok()?),
+                blob_offset: u32::from_le_bytes(bytes[8..12].try_into().ok()?),
+                blob_len: u32::from_le_bytes(bytes[12..16].try_into().ok()?),
+            })
+        }
+    }
+
+    /// Encodes `mappings` plus metadata into the on-disk layout described
+    /// above, sorted by `wasm_offset` so `lookup` can binary-search it.
+    pub(super) fn encode(
+        has_symbols: bool,
+        created_at: u64,
+        mappings: &HashMap<u64, SourceLocation>,
+    ) -> Result<Vec<u8>, String> {
+        let mut sorted: Vec<(u64, &SourceLocation)> =
+            mappings.iter().map(|(offset, loc)| (*offset, loc)).collect();
+        sorted.sort_by_key(|(offset, _)| *offset);
+
+        let index_region_start = HEADER_SIZE + sorted.len() * INDEX_ENTRY_SIZE;
+        let mut index = Vec::with_capacity(sorted.len());
+        let mut blobs = Vec::new();
+
+        for (offset, loc) in &sorted {
+            let blob = bincode::serialize(loc)
+                .map_err(|e| format!("Failed to serialize source location: {}", e))?;
+            let blob_offset = u32::try_from(index_region_start + blobs.len())
+                .map_err(|_| "cache file exceeds 4GB blob region".to_string())?;
+            let blob_len = u32::try_from(blob.len())
+                .map_err(|_| "a single source location blob exceeds 4GB".to_string())?;
+            index.push(IndexEntry { wasm_offset: *offset, blob_offset, blob_len });
+            blobs.extend_from_slice(&blob);
+        }
+
+        let mut body = Vec::with_capacity(sorted.len() * INDEX_ENTRY_SIZE + blobs.len());
+        for entry in &index {
+            body.extend_from_slice(&entry.to_bytes());
+        }
+        body.extend_from_slice(&blobs);
+
+        let header = Header {
+            magic: MAGIC,
+            version: VERSION,
+            has_symbols: u32::from(has_symbols),
+            entry_count: u32::try_from(sorted.len())
+                .map_err(|_| "too many mappings for one cache entry".to_string())?,
+            created_at,
+            checksum: Sha256::digest(&body).into(),
+        };
+
+        let mut out = Vec::with_capacity(HEADER_SIZE + body.len());
+        out.extend_from_slice(&header.to_bytes());
+        out.extend_from_slice(&body);
+        Ok(out)
+    }
+
+    /// Parses the header and index out of `bytes` (typically an mmap'd
+    /// file), without touching the blob region.
+    pub(super) fn parse_header_and_index(bytes: &[u8]) -> Option<(Header, Vec<IndexEntry>)> {
+        let header = Header::from_bytes(bytes)?;
+        let index_start = HEADER_SIZE;
+        let index_len = header.entry_count as usize * INDEX_ENTRY_SIZE;
+        let index_bytes = bytes.get(index_start..index_start + index_len)?;
+        let index = index_bytes
+            .chunks_exact(INDEX_ENTRY_SIZE)
+            .map(IndexEntry::from_bytes)
+            .collect::<Option<Vec<_>>>()?;
+        Some((header, index))
+    }
+
+    /// Recomputes the SHA256 over `bytes`' index+blob region and compares it
+    /// to the one `header` was written with, catching a truncated write or
+    /// bit-rot that still happens to parse cleanly.
+    pub(super) fn verify_checksum(bytes: &[u8], header: &Header) -> bool {
+        match bytes.get(HEADER_SIZE..) {
+            Some(body) => Sha256::digest(body).as_slice() == header.checksum,
+            None => false,
+        }
+    }
+}
+
+/// A memory-mapped, opened cache file. Only the header and index are parsed
+/// eagerly; [`Self::lookup`] deserializes a single [`SourceLocation`] blob on
+/// demand, and [`Self::load_all`] is there for callers that genuinely want
+/// the whole mapping table.
+pub struct CacheFileHandle {
+    mmap: memmap2::Mmap,
+    index: Vec<format::IndexEntry>,
+    has_symbols: bool,
+    created_at: u64,
+    checksum_valid: bool,
+}
+
+impl CacheFileHandle {
+    fn open(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        // Safety: the file is treated as read-only for the handle's
+        // lifetime; concurrent writers always replace it via the
+        // temp-file-then-rename path in `store`, never an in-place write, so
+        // this mapping never observes a torn write.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.ok()?;
+        let (header, index) = format::parse_header_and_index(&mmap)?;
+        let checksum_valid = format::verify_checksum(&mmap, &header);
+        Some(Self {
+            mmap,
+            index,
+            has_symbols: header.has_symbols != 0,
+            created_at: header.created_at,
+            checksum_valid,
+        })
+    }
+
+    pub fn has_symbols(&self) -> bool {
+        self.has_symbols
+    }
+
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Whether the stored checksum matches the actual index+blob bytes. A
+    /// successfully-parsed file can still fail this — e.g. a write truncated
+    /// partway through, or bit-rot — so callers that care about silent
+    /// corruption (rather than just a missing/stale-format file) should check
+    /// this before trusting [`Self::lookup`]/[`Self::load_all`].
+    pub fn checksum_valid(&self) -> bool {
+        self.checksum_valid
+    }
+
+    /// Binary-searches the index for `wasm_offset` and deserializes only the
+    /// matching blob.
+    pub fn lookup(&self, wasm_offset: u64) -> Option<SourceLocation> {
+        let idx = self
+            .index
+            .binary_search_by_key(&wasm_offset, |e| e.wasm_offset)
+            .ok()?;
+        self.deserialize_blob(self.index[idx])
+    }
+
+    /// Deserializes every blob into a full mapping table, for callers that
+    /// genuinely want the whole thing (e.g. `SourceMapper`'s in-memory cache).
+    pub fn load_all(&self) -> HashMap<u64, SourceLocation> {
+        self.index
+            .iter()
+            .filter_map(|entry| Some((entry.wasm_offset, self.deserialize_blob(*entry)?)))
+            .collect()
+    }
+
+    fn deserialize_blob(&self, entry: format::IndexEntry) -> Option<SourceLocation> {
+        let start = entry.blob_offset as usize;
+        let end = start.checked_add(entry.blob_len as usize)?;
+        let blob = self.mmap.get(start..end)?;
+        bincode::deserialize(blob).ok()
+    }
+}
+
 /// Cache entry containing parsed source mappings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceMapCacheEntry {
@@ -96,28 +380,124 @@ pub struct SourceMapCacheEntry {
 /// Source map cache manager
 pub struct SourceMapCache {
     cache_dir: PathBuf,
+    /// Hard expiry: an entry older than this is evicted and treated as a
+    /// cache miss. `None` means entries never expire by age alone.
+    max_age: Option<Duration>,
+    /// Soft expiry: an entry older than this (but still within `max_age`) is
+    /// still returned, but flagged `stale` so the caller can kick off a
+    /// background re-parse and `store` the replacement.
+    stale_after: Option<Duration>,
+    /// Size ceiling `store` enforces via [`Self::prune`] after every write, so
+    /// the cache directory doesn't grow unbounded across many contracts.
+    max_bytes: Option<u64>,
 }
 
 impl SourceMapCache {
-    /// Creates a new SourceMapCache with the default cache directory
-    pub fn new() -> Result<Self, String> {
+    /// Creates a new SourceMapCache, resolving the cache directory via
+    /// `ERST_CACHE_DIR`, then the platform cache-home env var, then
+    /// `~/.erst/cache/sourcemaps` — see [`Self::get_default_cache_dir`].
+    pub fn new() -> Result<Self, CacheDirError> {
         let cache_dir = Self::get_default_cache_dir()?;
-        Ok(Self { cache_dir })
+        Ok(Self { cache_dir, max_age: None, stale_after: None, max_bytes: None })
     }
 
-    /// Creates a new SourceMapCache with a custom cache directory
-    pub fn with_cache_dir(cache_dir: PathBuf) -> Result<Self, String> {
-        // Ensure the cache directory exists
-        fs::create_dir_all(&cache_dir)
-            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
-        Ok(Self { cache_dir })
+    /// Creates a new SourceMapCache with a custom cache directory, creating
+    /// it if needed and verifying it is writable.
+    pub fn with_cache_dir(cache_dir: PathBuf) -> Result<Self, CacheDirError> {
+        Self::ensure_writable(&cache_dir)?;
+        Ok(Self { cache_dir, max_age: None, stale_after: None, max_bytes: None })
+    }
+
+    /// Sets the hard expiry age. `get` deletes and treats as a miss any entry
+    /// older than `max_age`, guaranteeing correctness after the WASM is
+    /// rebuilt under the same hash or the source map parser improves.
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the soft expiry age for stale-while-revalidate: an entry older
+    /// than `stale_after` (but younger than `max_age`) is still returned from
+    /// `get`, with [`CacheHit::stale`] set so the caller can re-parse in the
+    /// background and `store` the refreshed entry.
+    #[must_use]
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = Some(stale_after);
+        self
+    }
+
+    /// Sets a size ceiling that `store` enforces by pruning the oldest
+    /// entries after every write, so the cache directory never grows past
+    /// `max_bytes` regardless of how many distinct contracts are simulated.
+    #[must_use]
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Resolves the default cache directory, trying each candidate in order
+    /// and using the first one that's writable (creating it if needed):
+    /// 1. `$ERST_CACHE_DIR`, for CI sandboxes, read-only homes, and shared
+    ///    team caches.
+    /// 2. The platform cache-home: `$XDG_CACHE_HOME/erst/sourcemaps` on
+    ///    Unix, `%LOCALAPPDATA%\erst\sourcemaps` on Windows.
+    /// 3. `~/.erst/cache/sourcemaps`, as before this env-aware resolution
+    ///    existed.
+    fn get_default_cache_dir() -> Result<PathBuf, CacheDirError> {
+        let candidates = Self::candidate_cache_dirs();
+        if candidates.is_empty() {
+            return Err(CacheDirError::NoHomeDirectory);
+        }
+
+        let mut last_err = None;
+        for candidate in candidates {
+            match Self::ensure_writable(&candidate) {
+                Ok(()) => return Ok(candidate),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        // Candidates were non-empty, so a loop iteration always ran and set this.
+        Err(last_err.expect("at least one candidate must have been tried"))
+    }
+
+    /// Cache directory candidates in resolution order. A candidate is only
+    /// included when its source (env var or home directory) actually
+    /// resolves; whether it's writable is checked separately by the caller.
+    fn candidate_cache_dirs() -> Vec<PathBuf> {
+        let mut candidates = Vec::new();
+
+        if let Some(dir) = std::env::var_os("ERST_CACHE_DIR").filter(|v| !v.is_empty()) {
+            candidates.push(PathBuf::from(dir));
+        }
+
+        #[cfg(unix)]
+        if let Some(xdg) = std::env::var_os("XDG_CACHE_HOME").filter(|v| !v.is_empty()) {
+            candidates.push(PathBuf::from(xdg).join("erst").join(CACHE_DIR_NAME));
+        }
+        #[cfg(windows)]
+        if let Some(local_app_data) = std::env::var_os("LOCALAPPDATA").filter(|v| !v.is_empty()) {
+            candidates.push(PathBuf::from(local_app_data).join("erst").join(CACHE_DIR_NAME));
+        }
+
+        if let Some(home) = dirs::home_dir() {
+            candidates.push(home.join(".erst").join("cache").join(CACHE_DIR_NAME));
+        }
+
+        candidates
     }
 
-    /// Gets the default cache directory (~/.erst/cache/sourcemaps)
-    fn get_default_cache_dir() -> Result<PathBuf, String> {
-        let home_dir =
-            dirs::home_dir().ok_or_else(|| "Failed to determine home directory".to_string())?;
-        Ok(home_dir.join(".erst").join("cache").join(CACHE_DIR_NAME))
+    /// Creates `dir` if needed and confirms it's writable by writing and
+    /// removing a throwaway probe file.
+    fn ensure_writable(dir: &Path) -> Result<(), CacheDirError> {
+        fs::create_dir_all(dir)
+            .map_err(|source| CacheDirError::NotWritable { path: dir.to_path_buf(), source })?;
+
+        let probe_path = dir.join(".write_test");
+        File::create(&probe_path)
+            .map_err(|source| CacheDirError::NotWritable { path: dir.to_path_buf(), source })?;
+        let _ = fs::remove_file(&probe_path);
+        Ok(())
     }
 
     /// Computes SHA256 hash of WASM bytes
@@ -157,23 +537,102 @@ impl SourceMapCache {
             .map_err(|e| format!("Failed to open lock file {:?}: {}", lock_path, e))
     }
 
+    /// Opens or creates the directory-wide advisory lock file used by
+    /// [`Self::prune`], distinct from the per-entry locks `get`/`store` take,
+    /// since pruning scans and removes entries other than the one being read
+    /// or written.
+    fn open_dir_lock_file(&self) -> Result<File, String> {
+        let lock_path = self.cache_dir.join(".gc.lock");
+        File::options()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| format!("Failed to open cache directory lock file {:?}: {}", lock_path, e))
+    }
+
     /// Gets a cached source map entry if it exists and is valid.
     /// When `no_cache` is true, skips the cache and returns None immediately,
     /// forcing the caller to re-parse WASM symbols from scratch.
-    pub fn get(&self, wasm_hash: &str, no_cache: bool) -> Option<SourceMapCacheEntry> {
+    ///
+    /// If `max_age` is set and the entry is older than it, the on-disk `.bin`
+    /// is deleted and this returns `None`, forcing a fresh parse. Otherwise
+    /// the entry's age is returned alongside it, with `stale` set once the
+    /// entry is older than `stale_after` — see [`Self::with_stale_after`].
+    ///
+    /// A checksum mismatch (a truncated write or bit-rot that still happens
+    /// to parse) is treated the same way: the bad file is deleted under an
+    /// exclusive lock and this returns `None`, turning silent corruption into
+    /// a self-healing cache miss instead of feeding wrong source locations
+    /// into a debugging session.
+    pub fn get(&self, wasm_hash: &str, no_cache: bool) -> Option<CacheHit> {
         if no_cache {
             println!("Cache bypassed via --no-cache flag. Re-parsing WASM symbols.");
             return None;
         }
 
         let cache_path = self.get_cache_path(wasm_hash);
+        let handle = self.open(wasm_hash)?;
+
+        if !handle.checksum_valid() {
+            eprintln!(
+                "Cache entry for WASM {} failed its integrity check (checksum mismatch); evicting",
+                &wasm_hash[..8]
+            );
+            if let Err(e) = self.evict(&cache_path) {
+                eprintln!("Failed to evict corrupt cache entry: {}", e);
+            }
+            return None;
+        }
+
+        let age = Self::age_from(handle.created_at());
+
+        if let Some(max_age) = self.max_age {
+            if age > max_age {
+                println!(
+                    "Cache entry for WASM {} is older than max_age ({:?} > {:?}); evicting",
+                    &wasm_hash[..8],
+                    age,
+                    max_age
+                );
+                if let Err(e) = self.evict(&cache_path) {
+                    eprintln!("Failed to evict expired cache entry: {}", e);
+                }
+                return None;
+            }
+        }
 
+        println!(
+            "Cache hit! Loading source map from cache for WASM: {}",
+            &wasm_hash[..8]
+        );
+        let stale = self.stale_after.is_some_and(|threshold| age > threshold);
+        let entry = SourceMapCacheEntry {
+            wasm_hash: wasm_hash.to_string(),
+            has_symbols: handle.has_symbols(),
+            mappings: handle.load_all(),
+            created_at: handle.created_at(),
+        };
+        Some(CacheHit { entry, age, stale })
+    }
+
+    /// Opens and mmaps the cache file for `wasm_hash`, parsing only its
+    /// header and index. Returns `None` for a missing file, one written in
+    /// an older format, or one whose header/index doesn't parse — all
+    /// treated identically as a cache miss by [`Self::get`]. A file that
+    /// parses but fails its checksum is still returned, with
+    /// [`CacheFileHandle::checksum_valid`] `false`.
+    pub fn open(&self, wasm_hash: &str) -> Option<CacheFileHandle> {
+        let cache_path = self.get_cache_path(wasm_hash);
         if !cache_path.exists() {
             return None;
         }
 
         // Acquire a shared OS-level lock so concurrent readers don't race with
-        // a writer that may be in the middle of replacing the file.
+        // a writer that may be in the middle of replacing the file. The
+        // replacement itself is atomic (temp file + rename), so the lock is
+        // held only long enough to establish the mapping.
         let lock_file = match Self::open_lock_file(&cache_path) {
             Ok(f) => f,
             Err(e) => {
@@ -186,37 +645,32 @@ impl SourceMapCache {
             return None;
         }
 
-        // Read and deserialize the cache file
-        let mut file = match File::open(&cache_path) {
-            Ok(f) => f,
-            Err(e) => {
-                eprintln!("Failed to open cache file: {}", e);
-                let _ = flock::unlock(&lock_file);
-                return None;
-            }
-        };
-
-        let mut bytes = Vec::new();
-        if let Err(e) = file.read_to_end(&mut bytes) {
-            eprintln!("Failed to read cache file: {}", e);
-            let _ = flock::unlock(&lock_file);
-            return None;
-        };
+        let handle = CacheFileHandle::open(&cache_path);
+        if handle.is_none() {
+            eprintln!("Failed to parse cache file header for WASM: {}", &wasm_hash[..8]);
+        }
+        let _ = flock::unlock(&lock_file);
+        handle
+    }
 
-        let result = match bincode::deserialize(&bytes) {
-            Ok(entry) => {
-                println!(
-                    "Cache hit! Loading source map from cache for WASM: {}",
-                    &wasm_hash[..8]
-                );
-                Some(entry)
-            }
-            Err(e) => {
-                eprintln!("Failed to deserialize cache entry: {}", e);
-                None
-            }
-        };
+    /// Age of a `created_at` Unix timestamp, measured to now. Clock skew or a
+    /// `created_at` in the future both clamp to zero rather than underflowing.
+    fn age_from(created_at: u64) -> Duration {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+        Duration::from_secs(now.saturating_sub(created_at))
+    }
 
+    /// Deletes `cache_path` under an exclusive lock, so a concurrent reader
+    /// never observes a half-removed file.
+    fn evict(&self, cache_path: &Path) -> Result<(), String> {
+        let lock_file = Self::open_lock_file(cache_path)?;
+        flock::lock_exclusive(&lock_file)?;
+        let result = fs::remove_file(cache_path)
+            .or_else(|e| if e.kind() == std::io::ErrorKind::NotFound { Ok(()) } else { Err(e) })
+            .map_err(|e| format!("Failed to remove expired cache file: {}", e));
         let _ = flock::unlock(&lock_file);
         result
     }
@@ -235,9 +689,8 @@ impl SourceMapCache {
         let lock_file = Self::open_lock_file(&cache_path)?;
         flock::lock_exclusive(&lock_file)?;
 
-        // Serialize the entry
-        let bytes = bincode::serialize(&entry)
-            .map_err(|e| format!("Failed to serialize cache entry: {}", e))?;
+        // Encode into the mmap-friendly header+index+blobs layout.
+        let bytes = format::encode(entry.has_symbols, entry.created_at, &entry.mappings)?;
 
         // Write atomically: write to a tmp file then rename to avoid readers
         // observing a partially-written file.
@@ -262,9 +715,56 @@ impl SourceMapCache {
         write_result?;
 
         println!("Cached source map for WASM: {}", &entry.wasm_hash[..8]);
+
+        if let Some(max_bytes) = self.max_bytes {
+            if let Err(e) = self.prune(max_bytes) {
+                eprintln!("Failed to prune cache after store: {}", e);
+            }
+        }
+
         Ok(())
     }
 
+    /// Evicts the oldest entries (by `created_at`) until the cache directory
+    /// is at or under `max_bytes`, returning how many entries were removed
+    /// and how many bytes were freed. Takes the directory-wide exclusive lock
+    /// for the whole scan-and-evict pass, so concurrent `prune` passes
+    /// (including the one `store` runs under `max_bytes`) are serialized. A
+    /// `store` write itself takes only its own entry's lock, not this one,
+    /// and is not held off by a running `prune`.
+    pub fn prune(&self, max_bytes: u64) -> Result<PruneResult, String> {
+        let dir_lock = self.open_dir_lock_file()?;
+        flock::lock_exclusive(&dir_lock)?;
+
+        let result = (|| {
+            let mut entries = self.list_cached()?;
+            entries.sort_by_key(|e| e.created_at);
+
+            let mut total_size: u64 = entries.iter().map(|e| e.file_size).sum();
+            let mut evicted = 0usize;
+            let mut bytes_freed = 0u64;
+
+            for entry in entries {
+                if total_size <= max_bytes {
+                    break;
+                }
+                let cache_path = self.get_cache_path(&entry.wasm_hash);
+                fs::remove_file(&cache_path)
+                    .map_err(|e| format!("Failed to evict cache file {:?}: {}", cache_path, e))?;
+                let _ = fs::remove_file(Self::get_lock_path(&cache_path));
+
+                total_size = total_size.saturating_sub(entry.file_size);
+                bytes_freed += entry.file_size;
+                evicted += 1;
+            }
+
+            Ok::<PruneResult, String>(PruneResult { evicted, bytes_freed })
+        })();
+
+        let _ = flock::unlock(&dir_lock);
+        result
+    }
+
     /// Clears all cached source maps
     pub fn clear(&self) -> Result<usize, String> {
         if !self.cache_dir.exists() {
@@ -326,23 +826,23 @@ impl SourceMapCache {
             let path = entry.path();
 
             if path.is_file() && path.extension().is_some_and(|ext| ext == "bin") {
-                // Read just the header to get metadata
-                if let Ok(mut file) = File::open(&path) {
-                    let mut bytes = Vec::new();
-                    if file.read_to_end(&mut bytes).is_ok() {
-                        if let Ok(cache_entry) = bincode::deserialize::<SourceMapCacheEntry>(&bytes)
-                        {
-                            let file_size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
-
-                            entries.push(CachedEntryInfo {
-                                wasm_hash: cache_entry.wasm_hash,
-                                has_symbols: cache_entry.has_symbols,
-                                mappings_count: cache_entry.mappings.len() as u64,
-                                created_at: cache_entry.created_at,
-                                file_size,
-                            });
-                        }
-                    }
+                // mmap just long enough to read the header and index; opening
+                // also checksums the index+blob bytes, but no blob is
+                // deserialized.
+                let Some(wasm_hash) = path.file_stem().map(|s| s.to_string_lossy().into_owned())
+                else {
+                    continue;
+                };
+                if let Some(handle) = CacheFileHandle::open(&path) {
+                    let file_size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+                    entries.push(CachedEntryInfo {
+                        wasm_hash,
+                        has_symbols: handle.has_symbols(),
+                        mappings_count: handle.entry_count() as u64,
+                        created_at: handle.created_at(),
+                        file_size,
+                    });
                 }
             }
         }
@@ -354,6 +854,48 @@ impl SourceMapCache {
     pub fn get_cache_dir(&self) -> &Path {
         &self.cache_dir
     }
+
+    /// Scans every cached entry's checksum without evicting anything,
+    /// reporting which ones are corrupt. Meant for a CLI `cache check`
+    /// command that wants to surface corruption to an operator rather than
+    /// silently fixing it on the next `get` — see [`Self::get`] for the
+    /// self-healing path that actually deletes a bad entry.
+    pub fn verify(&self) -> Result<Vec<CorruptEntryInfo>, String> {
+        if !self.cache_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut corrupt = Vec::new();
+        for entry in fs::read_dir(&self.cache_dir)
+            .map_err(|e| format!("Failed to read cache directory: {}", e))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+            let path = entry.path();
+
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "bin") {
+                let Some(wasm_hash) = path.file_stem().map(|s| s.to_string_lossy().into_owned())
+                else {
+                    continue;
+                };
+                match CacheFileHandle::open(&path) {
+                    Some(handle) if !handle.checksum_valid() => {
+                        corrupt.push(CorruptEntryInfo {
+                            wasm_hash,
+                            reason: "checksum mismatch".to_string(),
+                        });
+                    }
+                    Some(_) => {}
+                    None => corrupt.push(CorruptEntryInfo {
+                        wasm_hash,
+                        reason: "failed to parse header/index (corrupt or unsupported format)"
+                            .to_string(),
+                    }),
+                }
+            }
+        }
+
+        Ok(corrupt)
+    }
 }
 
 impl Default for SourceMapCache {
@@ -362,6 +904,27 @@ impl Default for SourceMapCache {
     }
 }
 
+/// A successful [`SourceMapCache::get`] lookup: the entry plus enough to let
+/// the caller implement stale-while-revalidate.
+#[derive(Debug, Clone)]
+pub struct CacheHit {
+    pub entry: SourceMapCacheEntry,
+    /// Time since `entry.created_at`.
+    pub age: Duration,
+    /// Set once `age` exceeds the cache's `stale_after` threshold but is
+    /// still within `max_age`. The entry is still usable immediately; the
+    /// caller should re-parse in the background and `store` the refresh.
+    pub stale: bool,
+}
+
+/// Outcome of a [`SourceMapCache::prune`] pass, for a CLI `cache gc` command
+/// to report to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneResult {
+    pub evicted: usize,
+    pub bytes_freed: u64,
+}
+
 /// Metadata about a cached entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedEntryInfo {
@@ -372,6 +935,14 @@ pub struct CachedEntryInfo {
     pub file_size: u64,
 }
 
+/// A cache entry [`SourceMapCache::verify`] found corrupt, for a CLI
+/// `cache check` command to report to the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorruptEntryInfo {
+    pub wasm_hash: String,
+    pub reason: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -433,9 +1004,55 @@ mod tests {
 
         // Retrieve the entry â€” no_cache=false so cache is used normally
         let retrieved = cache.get(&wasm_hash, false).unwrap();
-        assert_eq!(retrieved.wasm_hash, wasm_hash);
-        assert!(retrieved.has_symbols);
-        assert_eq!(retrieved.mappings.len(), 1);
+        assert_eq!(retrieved.entry.wasm_hash, wasm_hash);
+        assert!(retrieved.entry.has_symbols);
+        assert_eq!(retrieved.entry.mappings.len(), 1);
+        assert!(!retrieved.stale);
+    }
+
+    #[test]
+    fn test_max_age_evicts_expired_entry() {
+        let (cache, _temp) = create_test_cache();
+        let cache = cache.with_max_age(Duration::from_secs(60));
+
+        let wasm_bytes = vec![0x00, 0x61, 0x73, 0x6d];
+        let wasm_hash = SourceMapCache::compute_wasm_hash(&wasm_bytes);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        let entry = SourceMapCacheEntry {
+            wasm_hash: wasm_hash.clone(),
+            has_symbols: true,
+            mappings: HashMap::new(),
+            created_at: now - 3600,
+        };
+        cache.store(entry).unwrap();
+
+        assert!(cache.get(&wasm_hash, false).is_none(), "entry older than max_age must be a miss");
+        assert!(!cache.get_cache_path(&wasm_hash).exists(), "expired entry must be deleted from disk");
+    }
+
+    #[test]
+    fn test_stale_after_flags_entry_without_evicting() {
+        let (cache, _temp) = create_test_cache();
+        let cache = cache
+            .with_max_age(Duration::from_secs(3600))
+            .with_stale_after(Duration::from_secs(60));
+
+        let wasm_bytes = vec![0x00, 0x61, 0x73, 0x6d];
+        let wasm_hash = SourceMapCache::compute_wasm_hash(&wasm_bytes);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        let entry = SourceMapCacheEntry {
+            wasm_hash: wasm_hash.clone(),
+            has_symbols: true,
+            mappings: HashMap::new(),
+            created_at: now - 120,
+        };
+        cache.store(entry).unwrap();
+
+        let hit = cache.get(&wasm_hash, false).expect("entry within max_age must still hit");
+        assert!(hit.stale, "entry older than stale_after must be flagged stale");
+        assert!(cache.get_cache_path(&wasm_hash).exists(), "stale (not expired) entry must not be deleted");
     }
 
     #[test]
@@ -549,4 +1166,195 @@ mod tests {
         assert_eq!(list.len(), 1);
         assert_eq!(list[0].wasm_hash, wasm_hash);
     }
+
+    #[test]
+    fn test_prune_evicts_oldest_entries_first() {
+        let (cache, _temp) = create_test_cache();
+
+        // Three entries of equal size, oldest to newest.
+        for (i, created_at) in [100u64, 200, 300].into_iter().enumerate() {
+            let wasm_bytes = vec![0x00, 0x61, 0x73, 0x6d, i as u8];
+            let wasm_hash = SourceMapCache::compute_wasm_hash(&wasm_bytes);
+            let entry = SourceMapCacheEntry {
+                wasm_hash,
+                has_symbols: true,
+                mappings: HashMap::new(),
+                created_at,
+            };
+            cache.store(entry).unwrap();
+        }
+
+        let total_size = cache.get_cache_size().unwrap();
+        let per_entry = total_size / 3;
+
+        // Cap at just over two entries' worth, so exactly the oldest one must go.
+        let result = cache.prune(per_entry * 2 + 1).unwrap();
+        assert_eq!(result.evicted, 1);
+        assert!(result.bytes_freed > 0);
+
+        let remaining = cache.list_cached().unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.iter().all(|e| e.created_at >= 200));
+    }
+
+    #[test]
+    fn test_store_prunes_to_configured_max_bytes() {
+        let (cache, _temp) = create_test_cache();
+
+        let first = SourceMapCacheEntry {
+            wasm_hash: SourceMapCache::compute_wasm_hash(&[0x00]),
+            has_symbols: true,
+            mappings: HashMap::new(),
+            created_at: 100,
+        };
+        cache.store(first).unwrap();
+        let entry_size = cache.list_cached().unwrap()[0].file_size;
+
+        // Reconfigure with a cap that only fits one entry, then store a second.
+        let cache = cache.with_max_bytes(entry_size + 1);
+        let second = SourceMapCacheEntry {
+            wasm_hash: SourceMapCache::compute_wasm_hash(&[0x01]),
+            has_symbols: true,
+            mappings: HashMap::new(),
+            created_at: 200,
+        };
+        cache.store(second).unwrap();
+
+        let list = cache.list_cached().unwrap();
+        assert_eq!(list.len(), 1, "store must prune older entries to stay under max_bytes");
+        assert_eq!(list[0].created_at, 200);
+    }
+
+    #[test]
+    fn test_open_lookup_resolves_single_offset_without_loading_all() {
+        let (cache, _temp) = create_test_cache();
+
+        let wasm_bytes = vec![0x00, 0x61, 0x73, 0x6d];
+        let wasm_hash = SourceMapCache::compute_wasm_hash(&wasm_bytes);
+
+        let mut mappings = HashMap::new();
+        mappings.insert(0x10, SourceLocation { file: "a.rs".to_string(), line: 1, column: None, column_end: None, github_link: None });
+        mappings.insert(0x20, SourceLocation { file: "b.rs".to_string(), line: 2, column: None, column_end: None, github_link: None });
+
+        let entry = SourceMapCacheEntry { wasm_hash: wasm_hash.clone(), has_symbols: true, mappings, created_at: 1234567890 };
+        cache.store(entry).unwrap();
+
+        let handle = cache.open(&wasm_hash).expect("must open a freshly stored cache file");
+        assert_eq!(handle.entry_count(), 2);
+        assert_eq!(handle.lookup(0x20).map(|l| l.file), Some("b.rs".to_string()));
+        assert!(handle.lookup(0x99).is_none());
+
+        let all = handle.load_all();
+        assert_eq!(all.len(), 2);
+    }
+
+    #[test]
+    fn test_old_format_bin_file_is_treated_as_miss() {
+        let (cache, _temp) = create_test_cache();
+
+        let wasm_hash = SourceMapCache::compute_wasm_hash(&[0x00]);
+        fs::create_dir_all(cache.get_cache_dir()).unwrap();
+        let legacy_path = cache.get_cache_dir().join(format!("{}.bin", wasm_hash));
+
+        // The format this module used before the mmap header/index layout:
+        // a plain whole-struct bincode blob with no magic/version prefix.
+        let legacy_entry = SourceMapCacheEntry {
+            wasm_hash: wasm_hash.clone(),
+            has_symbols: true,
+            mappings: HashMap::new(),
+            created_at: 1234567890,
+        };
+        let legacy_bytes = bincode::serialize(&legacy_entry).unwrap();
+        fs::write(&legacy_path, legacy_bytes).unwrap();
+
+        assert!(cache.open(&wasm_hash).is_none(), "pre-mmap-format file must not be mis-parsed as valid");
+        assert!(cache.get(&wasm_hash, false).is_none());
+    }
+
+    /// Flips a byte deep enough into the file to land in the blob region
+    /// regardless of how many mappings precede it, without touching the
+    /// header's magic/version so the file still parses.
+    fn corrupt_last_byte(path: &Path) {
+        let mut bytes = fs::read(path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_get_evicts_and_misses_on_checksum_mismatch() {
+        let (cache, _temp) = create_test_cache();
+
+        let wasm_hash = SourceMapCache::compute_wasm_hash(&[0x00]);
+        let mut mappings = HashMap::new();
+        mappings.insert(0x10, SourceLocation { file: "a.rs".to_string(), line: 1, column: None, column_end: None, github_link: None });
+        let entry = SourceMapCacheEntry { wasm_hash: wasm_hash.clone(), has_symbols: true, mappings, created_at: 1234567890 };
+        cache.store(entry).unwrap();
+
+        let cache_path = cache.get_cache_dir().join(format!("{}.bin", wasm_hash));
+        corrupt_last_byte(&cache_path);
+
+        assert!(cache.get(&wasm_hash, false).is_none(), "a checksum mismatch must be treated as a cache miss");
+        assert!(!cache_path.exists(), "the corrupt file must be deleted so the next store starts clean");
+    }
+
+    #[test]
+    fn test_verify_reports_corrupt_entries_without_deleting_them() {
+        let (cache, _temp) = create_test_cache();
+
+        let good_hash = SourceMapCache::compute_wasm_hash(&[0x00]);
+        let bad_hash = SourceMapCache::compute_wasm_hash(&[0x01]);
+        for hash in [&good_hash, &bad_hash] {
+            let mut mappings = HashMap::new();
+            mappings.insert(0x10, SourceLocation { file: "a.rs".to_string(), line: 1, column: None, column_end: None, github_link: None });
+            let entry = SourceMapCacheEntry { wasm_hash: hash.clone(), has_symbols: true, mappings, created_at: 1234567890 };
+            cache.store(entry).unwrap();
+        }
+        corrupt_last_byte(&cache.get_cache_dir().join(format!("{}.bin", bad_hash)));
+
+        let corrupt = cache.verify().unwrap();
+
+        assert_eq!(corrupt.len(), 1);
+        assert_eq!(corrupt[0].wasm_hash, bad_hash);
+        assert!(cache.get_cache_dir().join(format!("{}.bin", bad_hash)).exists(), "verify must not delete anything");
+    }
+
+    #[test]
+    fn test_candidate_cache_dirs_prefers_erst_cache_dir_env() {
+        let temp_dir = TempDir::new().unwrap();
+        std::env::set_var("ERST_CACHE_DIR", temp_dir.path());
+
+        let candidates = SourceMapCache::candidate_cache_dirs();
+
+        std::env::remove_var("ERST_CACHE_DIR");
+
+        assert_eq!(candidates.first(), Some(&temp_dir.path().to_path_buf()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_with_cache_dir_reports_not_writable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Root bypasses permission bits, so this check is meaningless there.
+        if unsafe { libc::geteuid() } == 0 {
+            return;
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let read_only_parent = temp_dir.path().join("locked");
+        fs::create_dir_all(&read_only_parent).unwrap();
+        fs::set_permissions(&read_only_parent, fs::Permissions::from_mode(0o500)).unwrap();
+
+        let target = read_only_parent.join("sourcemaps");
+        let result = SourceMapCache::with_cache_dir(target.clone());
+
+        // Restore permissions so TempDir can clean up on drop.
+        fs::set_permissions(&read_only_parent, fs::Permissions::from_mode(0o700)).unwrap();
+
+        match result {
+            Err(CacheDirError::NotWritable { path, .. }) => assert_eq!(path, target),
+            other => panic!("expected NotWritable, got {:?}", other),
+        }
+    }
 }
@@ -25,117 +25,326 @@
     clippy::map_unwrap_or
 )]
 
-use crate::source_map_cache::SourceMapCache;
+use crate::source_map_cache::{SourceMapCache, SourceMapCacheEntry};
 use object::{Object, ObjectSection};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct SourceMapper {
     debug_line_data: Option<Vec<u8>>,
+    debug_info_data: Option<Vec<u8>>,
+    debug_abbrev_data: Option<Vec<u8>>,
+    /// DWARF5 `.debug_line_str`: holds path strings referenced from the line
+    /// program via `DW_FORM_line_strp` instead of being inlined.
+    debug_line_str_data: Option<Vec<u8>>,
+    /// `.debug_str`: holds strings referenced via `DW_FORM_strp`, shared
+    /// between `.debug_info` and (in DWARF5) the line program.
+    debug_str_data: Option<Vec<u8>>,
     has_symbols: bool,
     wasm_hash: String,
     cached_mappings: Option<HashMap<u64, SourceLocation>>,
+    /// Sorted `[start, end)` address ranges built once from `.debug_line` at
+    /// construction time; see [`build_line_table`].
+    line_table: Vec<LineRow>,
+    /// `.debug_line`'s file-name table, built alongside `line_table`; used to
+    /// resolve `DW_AT_call_file` indices when synthesizing inline frames.
+    file_table: FileTable,
+    /// Cache used by this mapper's constructor, retained so
+    /// [`Self::with_path_remap`] can re-persist the remapped line table under
+    /// the same WASM hash. `None` for [`Self::new_without_cache`].
+    cache: Option<SourceMapCache>,
+    /// Ordered `(from_prefix, to_prefix)` pairs set via
+    /// [`Self::with_path_remap`]; see [`remap_path`].
+    path_remaps: Vec<(String, String)>,
+    /// Memoized [`Self::map_wasm_offset_to_frames`] results, keyed by
+    /// `wasm_offset`. `RefCell` because the DIE-tree walk it caches is pure
+    /// given an immutable `&self`.
+    frame_cache: RefCell<HashMap<u64, Vec<SourceLocation>>>,
+}
+
+/// Grouping of the raw DWARF sections located during construction, so the
+/// constructors don't have to thread an ever-growing tuple around.
+struct ParsedSections {
+    has_symbols: bool,
+    wasm_hash: String,
+    debug_line: Option<Vec<u8>>,
+    debug_info: Option<Vec<u8>>,
+    debug_abbrev: Option<Vec<u8>>,
+    debug_line_str: Option<Vec<u8>>,
+    debug_str: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceLocation {
     pub file: String,
     pub line: u32,
-    pub column: u32,
+    /// Column within `line`, when the line program records one. `None`
+    /// covers both an absent `DW_LNS_set_column` and the DWARF `LeftEdge`
+    /// convention of an explicit column 0 — both mean "point at the
+    /// statement, not a specific subexpression."
+    pub column: Option<u32>,
     pub column_end: Option<u32>,
+    /// Name of the enclosing function, when it could be resolved from
+    /// `.debug_info` (following `DW_AT_specification`/`DW_AT_abstract_origin`
+    /// links to find the name attached to a separate declaration DIE).
+    pub function: Option<String>,
 }
 
 impl SourceMapper {
     /// Creates a new SourceMapper with caching enabled
     pub fn new(wasm_bytes: Vec<u8>) -> Self {
-        let has_symbols = Self::check_debug_symbols(&wasm_bytes);
-        let wasm_hash = SourceMapCache::compute_wasm_hash(&wasm_bytes);
-        let debug_line_data = has_symbols
-            .then(|| Self::extract_debug_line(&wasm_bytes))
-            .flatten();
-
-        // Try to load from cache first
-        let cached_mappings = if let Ok(cache) = SourceMapCache::new() {
-            if let Some(entry) = cache.get(&wasm_hash) {
-                if entry.has_symbols == has_symbols {
-                    Some(entry.mappings)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        let sections = Self::parse_sections(&wasm_bytes);
+        let cache = SourceMapCache::new().ok();
+        let cached_mappings = Self::load_cached_mappings(cache.as_ref(), &sections);
+        let (file_table, line_table) = Self::build_line_table_for(&sections);
+        if cached_mappings.is_none() {
+            Self::persist_line_table(cache.as_ref(), &sections, &line_table);
+        }
 
         Self {
-            debug_line_data,
-            has_symbols,
-            wasm_hash,
+            debug_line_data: sections.debug_line,
+            debug_info_data: sections.debug_info,
+            debug_abbrev_data: sections.debug_abbrev,
+            debug_line_str_data: sections.debug_line_str,
+            debug_str_data: sections.debug_str,
+            has_symbols: sections.has_symbols,
+            wasm_hash: sections.wasm_hash,
             cached_mappings,
+            line_table,
+            file_table,
+            cache,
+            path_remaps: Vec::new(),
+            frame_cache: RefCell::new(HashMap::new()),
         }
     }
 
     /// Creates a new SourceMapper without caching (for testing)
     pub fn new_without_cache(wasm_bytes: Vec<u8>) -> Self {
-        let has_symbols = Self::check_debug_symbols(&wasm_bytes);
-        let wasm_hash = SourceMapCache::compute_wasm_hash(&wasm_bytes);
-        let debug_line_data = has_symbols
-            .then(|| Self::extract_debug_line(&wasm_bytes))
-            .flatten();
+        let sections = Self::parse_sections(&wasm_bytes);
+        let (file_table, line_table) = Self::build_line_table_for(&sections);
         Self {
-            debug_line_data,
-            has_symbols,
-            wasm_hash,
+            debug_line_data: sections.debug_line,
+            debug_info_data: sections.debug_info,
+            debug_abbrev_data: sections.debug_abbrev,
+            debug_line_str_data: sections.debug_line_str,
+            debug_str_data: sections.debug_str,
+            has_symbols: sections.has_symbols,
+            wasm_hash: sections.wasm_hash,
             cached_mappings: None,
+            line_table,
+            file_table,
+            cache: None,
+            path_remaps: Vec::new(),
+            frame_cache: RefCell::new(HashMap::new()),
         }
     }
 
     /// Creates a new SourceMapper with a custom cache directory (for testing)
     pub fn new_with_cache(wasm_bytes: Vec<u8>, cache_dir: std::path::PathBuf) -> Self {
-        let has_symbols = Self::check_debug_symbols(&wasm_bytes);
-        let wasm_hash = SourceMapCache::compute_wasm_hash(&wasm_bytes);
-        let debug_line_data = has_symbols
-            .then(|| Self::extract_debug_line(&wasm_bytes))
-            .flatten();
-
-        // Try to load from cache first
-        let cached_mappings = if let Ok(cache) = SourceMapCache::with_cache_dir(cache_dir) {
-            if let Some(entry) = cache.get(&wasm_hash) {
-                if entry.has_symbols == has_symbols {
-                    Some(entry.mappings)
-                } else {
-                    None
-                }
-            } else {
-                None
+        let sections = Self::parse_sections(&wasm_bytes);
+        let cache = SourceMapCache::with_cache_dir(cache_dir).ok();
+        let cached_mappings = Self::load_cached_mappings(cache.as_ref(), &sections);
+        let (file_table, line_table) = Self::build_line_table_for(&sections);
+        if cached_mappings.is_none() {
+            Self::persist_line_table(cache.as_ref(), &sections, &line_table);
+        }
+
+        Self {
+            debug_line_data: sections.debug_line,
+            debug_info_data: sections.debug_info,
+            debug_abbrev_data: sections.debug_abbrev,
+            debug_line_str_data: sections.debug_line_str,
+            debug_str_data: sections.debug_str,
+            has_symbols: sections.has_symbols,
+            wasm_hash: sections.wasm_hash,
+            cached_mappings,
+            line_table,
+            file_table,
+            cache,
+            path_remaps: Vec::new(),
+            frame_cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Registers `remaps` (ordered `(from_prefix, to_prefix)` pairs) so every
+    /// `SourceLocation.file` this mapper has already produced — and every one
+    /// it produces from here on — has its first matching prefix replaced.
+    ///
+    /// Absolute compilation paths baked into `.debug_line` (e.g.
+    /// `/home/alice/project/src/lib.rs`) make `file` values machine-specific,
+    /// which poisons a `SourceMapCache` shared across users and CI. Remapping
+    /// `/home/alice/project` -> `/src` yields a stable, relocatable path
+    /// instead.
+    ///
+    /// Re-persists the remapped line table to this mapper's cache (if any)
+    /// under the same WASM hash, so the stored mappings are normalized and
+    /// remain valid regardless of where the build happened.
+    #[must_use]
+    pub fn with_path_remap(mut self, remaps: Vec<(String, String)>) -> Self {
+        self.path_remaps = remaps;
+        if self.path_remaps.is_empty() {
+            return self;
+        }
+
+        if let Some(debug_line) = self.debug_line_data.as_deref() {
+            let (file_table, line_table) = build_line_table(
+                debug_line,
+                self.debug_line_str_data.as_deref(),
+                self.debug_str_data.as_deref(),
+                &self.path_remaps,
+            );
+            self.file_table = file_table;
+            self.line_table = line_table;
+        }
+        if let Some(cached) = self.cached_mappings.as_mut() {
+            for loc in cached.values_mut() {
+                loc.file = remap_path(&loc.file, &self.path_remaps);
             }
-        } else {
-            None
+        }
+        // Frames memoized under the old (unremapped) paths are now stale.
+        self.frame_cache.borrow_mut().clear();
+
+        self.persist_remapped_line_table();
+        self
+    }
+
+    /// Re-stores `self.line_table` (already built with `self.path_remaps`
+    /// applied) under `self.wasm_hash`, overwriting whatever was persisted
+    /// during construction — see [`Self::with_path_remap`].
+    fn persist_remapped_line_table(&self) {
+        let Some(cache) = self.cache.as_ref() else { return };
+        if !self.has_symbols || self.line_table.is_empty() {
+            return;
+        }
+
+        let mappings = self
+            .line_table
+            .iter()
+            .map(|row| (row.start_addr, row.location.clone()))
+            .collect();
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let entry = SourceMapCacheEntry {
+            wasm_hash: self.wasm_hash.clone(),
+            has_symbols: true,
+            mappings,
+            created_at,
         };
+        if let Err(e) = cache.store(entry) {
+            eprintln!("Failed to persist remapped source map to cache: {}", e);
+        }
+    }
 
-        Self {
-            debug_line_data,
+    /// Shared section-discovery logic for all constructors: checks for debug
+    /// symbols, computes the WASM hash, and extracts the raw `.debug_line`,
+    /// `.debug_info`, `.debug_abbrev`, `.debug_line_str`, and `.debug_str`
+    /// custom sections. The latter two are DWARF5-only and are `None` for
+    /// older toolchains, which keep all strings inline in `.debug_line`.
+    fn parse_sections(wasm_bytes: &[u8]) -> ParsedSections {
+        let has_symbols = Self::check_debug_symbols(wasm_bytes);
+        let wasm_hash = SourceMapCache::compute_wasm_hash(wasm_bytes);
+        let extract = |name| has_symbols.then(|| Self::extract_section(wasm_bytes, name)).flatten();
+        ParsedSections {
             has_symbols,
             wasm_hash,
-            cached_mappings,
+            debug_line: extract(".debug_line"),
+            debug_info: extract(".debug_info"),
+            debug_abbrev: extract(".debug_abbrev"),
+            debug_line_str: extract(".debug_line_str"),
+            debug_str: extract(".debug_str"),
         }
     }
 
+    /// Checks for debug symbols in any object format `object` understands
+    /// (WASM, ELF, Mach-O, PE/COFF) — `object::File::parse` auto-detects the
+    /// container, so native crash dumps symbolize through the same pipeline
+    /// as WASM without any format-specific branching here.
     fn check_debug_symbols(wasm_bytes: &[u8]) -> bool {
         if let Ok(obj_file) = object::File::parse(wasm_bytes) {
-            obj_file.section_by_name(".debug_info").is_some()
-                && obj_file.section_by_name(".debug_line").is_some()
+            Self::find_section_data(&obj_file, ".debug_info").is_some()
+                && Self::find_section_data(&obj_file, ".debug_line").is_some()
         } else {
             false
         }
     }
 
-    fn extract_debug_line(wasm_bytes: &[u8]) -> Option<Vec<u8>> {
+    /// Locates `name` in `obj` and returns its (transparently decompressed)
+    /// contents, falling back to the legacy GNU `.zdebug_*` naming
+    /// convention (e.g. `.zdebug_line`) some older toolchains use for
+    /// compressed debug sections instead of the gABI `SHF_COMPRESSED` flag.
+    /// `uncompressed_data` handles both the gABI compression header
+    /// (`ELFCOMPRESS_ZLIB`/`_ZSTD`) and the legacy "ZLIB" + size-prefix
+    /// format transparently, so no format-specific branching is needed here.
+    fn find_section_data(obj: &object::File<'_>, name: &str) -> Option<Vec<u8>> {
+        let section = obj.section_by_name(name).or_else(|| {
+            let gnu_name = format!(".z{}", name.trim_start_matches('.'));
+            obj.section_by_name(&gnu_name)
+        })?;
+        section.uncompressed_data().ok().map(|d| d.into_owned())
+    }
+
+    /// Extracts `name` from `wasm_bytes` (WASM, ELF, Mach-O, or PE — whatever
+    /// `object` can parse), decompressing it if needed. The returned buffer
+    /// is always owned, independent of the original section's lifetime.
+    fn extract_section(wasm_bytes: &[u8], name: &str) -> Option<Vec<u8>> {
         let obj = object::File::parse(wasm_bytes).ok()?;
-        let section = obj.section_by_name(".debug_line")?;
-        section.data().ok().map(|d| d.to_vec())
+        Self::find_section_data(&obj, name)
+    }
+
+    /// Builds the sorted address index (and its backing file table) from
+    /// `sections.debug_line`, or both empty when there's no `.debug_line` to
+    /// parse.
+    fn build_line_table_for(sections: &ParsedSections) -> (FileTable, Vec<LineRow>) {
+        sections.debug_line.as_deref().map_or_else(
+            || (FileTable::default(), Vec::new()),
+            |data| {
+                build_line_table(data, sections.debug_line_str.as_deref(), sections.debug_str.as_deref(), &[])
+            },
+        )
+    }
+
+    /// Looks up `sections.wasm_hash` in `cache`, returning the cached flat
+    /// mapping table when it's a hit for a WASM with the same symbol status.
+    fn load_cached_mappings(
+        cache: Option<&SourceMapCache>,
+        sections: &ParsedSections,
+    ) -> Option<HashMap<u64, SourceLocation>> {
+        let hit = cache?.get(&sections.wasm_hash, false)?;
+        (hit.entry.has_symbols == sections.has_symbols).then_some(hit.entry.mappings)
+    }
+
+    /// Flattens a freshly-built `line_table` into `SourceMapCache`, so the
+    /// next process to construct a `SourceMapper` for this same WASM hash
+    /// gets an O(log n)-free cache hit instead of re-walking the whole line
+    /// program. Only runs on a cache miss (`load_cached_mappings` returned
+    /// `None`); a cache hit's mappings are already on disk.
+    fn persist_line_table(cache: Option<&SourceMapCache>, sections: &ParsedSections, line_table: &[LineRow]) {
+        let Some(cache) = cache else { return };
+        if !sections.has_symbols || line_table.is_empty() {
+            return;
+        }
+
+        let mappings = line_table
+            .iter()
+            .map(|row| (row.start_addr, row.location.clone()))
+            .collect();
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let entry = SourceMapCacheEntry {
+            wasm_hash: sections.wasm_hash.clone(),
+            has_symbols: sections.has_symbols,
+            mappings,
+            created_at,
+        };
+        if let Err(e) = cache.store(entry) {
+            eprintln!("Failed to persist source map to cache: {}", e);
+        }
     }
 
     pub fn map_wasm_offset_to_source(&self, wasm_offset: u64) -> Option<SourceLocation> {
@@ -146,9 +355,74 @@ impl SourceMapper {
             }
         }
 
-        // Fall back to real DWARF .debug_line parsing
+        // Fall back to the prebuilt sorted address index
         // TODO: iterate all CUs from .debug_info offsets for multi-CU WASM
-        parse_debug_line(self.debug_line_data.as_deref()?, wasm_offset)
+        let mut location = lookup_line_table(&self.line_table, wasm_offset)?;
+        location.function = self.function_name_for_offset(wasm_offset);
+        Some(location)
+    }
+
+    /// Resolves the name of the function enclosing `wasm_offset` by locating
+    /// its `DW_TAG_subprogram` DIE in `.debug_info` and reading `DW_AT_name`,
+    /// following `DW_AT_specification`/`DW_AT_abstract_origin` when the name
+    /// is attached to a separate declaration DIE. The raw DWARF name is passed
+    /// through [`demangle_symbol`], which decodes Rust-mangled names only.
+    fn function_name_for_offset(&self, wasm_offset: u64) -> Option<String> {
+        let info = self.debug_info_data.as_deref()?;
+        let abbrev = self.debug_abbrev_data.as_deref()?;
+        let name = dwarf::function_name_for_address(info, abbrev, wasm_offset)?;
+        Some(demangle_symbol(&name))
+    }
+
+    /// Returns the full inline call stack for `wasm_offset`, innermost frame
+    /// first. The innermost frame's file/line come from the `.debug_line`
+    /// lookup as in `map_wasm_offset_to_source`; each enclosing inline frame
+    /// is synthesized from the `DW_AT_call_file`/`DW_AT_call_line` of the
+    /// `DW_TAG_inlined_subroutine` DIE that encloses `wasm_offset`, walking
+    /// outward from the deepest match in the `.debug_info` DIE tree.
+    ///
+    /// Results are memoized in `frame_cache` keyed by `wasm_offset`, since a
+    /// symbolizer commonly re-resolves the same handful of crash addresses
+    /// (e.g. across retries or when rendering several reports from one run).
+    pub fn map_wasm_offset_to_frames(&self, wasm_offset: u64) -> Vec<SourceLocation> {
+        if let Some(frames) = self.frame_cache.borrow().get(&wasm_offset) {
+            return frames.clone();
+        }
+
+        let frames = self.compute_frames(wasm_offset);
+        self.frame_cache.borrow_mut().insert(wasm_offset, frames.clone());
+        frames
+    }
+
+    /// Does the actual DIE-tree walk behind [`Self::map_wasm_offset_to_frames`];
+    /// split out so the public method only has to deal with the memoization.
+    fn compute_frames(&self, wasm_offset: u64) -> Vec<SourceLocation> {
+        let Some(innermost) = self.map_wasm_offset_to_source(wasm_offset) else {
+            return Vec::new();
+        };
+
+        let Some(info) = self.debug_info_data.as_deref() else {
+            return vec![innermost];
+        };
+        let Some(abbrev) = self.debug_abbrev_data.as_deref() else {
+            return vec![innermost];
+        };
+
+        // TODO: fall back to DW_AT_ranges when a DIE has no DW_AT_low_pc/
+        // DW_AT_high_pc pair (non-contiguous inlined ranges), once the
+        // .debug_ranges/.debug_rnglists parsing lands.
+        let Some(chain) =
+            dwarf::inline_chain_for_address(info, abbrev, wasm_offset, &self.file_table, &self.path_remaps)
+        else {
+            return vec![innermost];
+        };
+
+        let mut frames = vec![SourceLocation {
+            function: chain.innermost_function,
+            ..innermost
+        }];
+        frames.extend(chain.enclosing.into_iter());
+        frames
     }
 
     pub fn has_debug_symbols(&self) -> bool {
@@ -161,20 +435,120 @@ impl SourceMapper {
     }
 }
 
-// Parses a DWARF32 v2-v5 .debug_line section (little-endian) and returns the
-// SourceLocation for `target_addr`, or None if not found or on any parse error.
-// Only the opcode subset emitted by gimli::write for a simple line program is
-// required; unsupported opcodes are skipped by consuming their operand bytes.
-fn parse_debug_line(data: &[u8], target_addr: u64) -> Option<SourceLocation> {
+/// One row of the flattened, sorted line table: `[start_addr, end_addr)` maps
+/// to `location`. Built once per `.debug_line` section and binary-searched on
+/// every lookup instead of re-running the line-program state machine per call.
+struct LineRow {
+    start_addr: u64,
+    end_addr: u64,
+    location: SourceLocation,
+}
+
+/// The `include_directories`/`file_names` tables parsed from a `.debug_line`
+/// unit header, kept alongside the line table so `DW_AT_call_file` indices in
+/// `.debug_info` (read while resolving an inline call chain) can be turned
+/// into the same file paths the line program itself produces.
+#[derive(Default)]
+struct FileTable {
+    file_names: Vec<(String, usize)>,
+    include_dirs: Vec<String>,
+}
+
+impl FileTable {
+    /// Resolves `file_idx` the same way [`build_location`] does: joining the
+    /// file name with its directory entry, or `None` if the index is out of
+    /// range.
+    fn path_for(&self, file_idx: usize) -> Option<String> {
+        let (file_name, dir_idx) = self.file_names.get(file_idx)?;
+        let dir = self.include_dirs.get(*dir_idx).map(String::as_str).unwrap_or("");
+        Some(if dir.is_empty() {
+            file_name.clone()
+        } else {
+            format!("{}/{}", dir, file_name)
+        })
+    }
+}
+
+/// Runs the DWARF v2-v5 `.debug_line` state machine over every compilation
+/// unit in `data`, merging the `LineRow`s each one produces into a single
+/// table sorted by `start_addr` so callers can `binary_search_by` instead of
+/// re-parsing the program on every offset lookup. `FileTable` is taken from
+/// the first unit only -- resolving `DW_AT_call_file` against later units is
+/// tracked by the `dwarf` submodule's own leading-CU limitation below.
+//
+// `debug_line_str`/`debug_str` back DWARF5's `DW_FORM_line_strp`/`DW_FORM_strp`
+// path strings (rustc emits v5 by default as of recent toolchains, where
+// paths are no longer inlined in `.debug_line`). v5 also renumbers file
+// indices from 0 instead of 1 (index 0 is the primary source file rather
+// than being unused) -- `file_names`/`file_idx`'s initial value are built
+// per-version below so `build_location`/`FileTable::path_for` don't need to
+// know which version produced the table they're indexing into.
+//
+// TODO: mirror `.debug_aranges` CU-level bucketing now that multi-CU
+// binaries are supported, so a lookup doesn't have to scan rows from
+// unrelated units.
+fn build_line_table(
+    data: &[u8],
+    debug_line_str: Option<&[u8]>,
+    debug_str: Option<&[u8]>,
+    path_remaps: &[(String, String)],
+) -> (FileTable, Vec<LineRow>) {
+    build_line_table_inner(data, debug_line_str, debug_str, path_remaps).unwrap_or_default()
+}
+
+fn build_line_table_inner(
+    data: &[u8],
+    debug_line_str: Option<&[u8]>,
+    debug_str: Option<&[u8]>,
+    path_remaps: &[(String, String)],
+) -> Option<(FileTable, Vec<LineRow>)> {
     let mut pos = 0usize;
+    let mut rows: Vec<LineRow> = Vec::new();
+    let mut first_file_table: Option<FileTable> = None;
+
+    while pos < data.len() {
+        let (file_table, mut unit_rows, unit_end) =
+            parse_line_unit(data, pos, debug_line_str, debug_str, path_remaps)?;
+        rows.append(&mut unit_rows);
+        if first_file_table.is_none() {
+            first_file_table = Some(file_table);
+        }
+        pos = unit_end;
+    }
+
+    let file_table = first_file_table?;
+    rows.sort_by_key(|row| row.start_addr);
+    Some((file_table, rows))
+}
+
+/// Parses the single `.debug_line` compilation unit starting at `start`,
+/// returning its file table, the rows it produced, and the position right
+/// after it (`unit_end`) so [`build_line_table_inner`] can continue to the
+/// next unit.
+fn parse_line_unit(
+    data: &[u8],
+    start: usize,
+    debug_line_str: Option<&[u8]>,
+    debug_str: Option<&[u8]>,
+    path_remaps: &[(String, String)],
+) -> Option<(FileTable, Vec<LineRow>, usize)> {
+    let mut pos = start;
 
     // --- Unit header ---
-    // unit_length (DWARF32: 4 bytes; skip 64-bit DWARF which begins with 0xffffffff)
-    let unit_length = read_u32_le(data, pos)? as usize;
-    if unit_length == 0xffff_ffff {
-        return None; // 64-bit DWARF not supported
-    }
+    // unit_length: a plain 4-byte length for 32-bit DWARF, or -- when those
+    // 4 bytes read as the reserved `0xffffffff` escape value -- the 64-bit
+    // DWARF format, whose real length follows as 8 bytes and whose
+    // `header_length`/section-offset fields are all 8 bytes wide too.
+    let initial_length = read_u32_le(data, pos)?;
     pos += 4;
+    let (unit_length, is_64bit) = if initial_length == 0xffff_ffff {
+        (read_u64_le(data, pos)? as usize, true)
+    } else {
+        (initial_length as usize, false)
+    };
+    if is_64bit {
+        pos += 8;
+    }
 
     let unit_end = pos + unit_length;
     if unit_end > data.len() {
@@ -187,10 +561,31 @@ fn parse_debug_line(data: &[u8], target_addr: u64) -> Option<SourceLocation> {
         return None;
     }
 
-    // header_length (4 bytes for DWARF32)
-    let header_length = read_u32_le(data, pos)? as usize;
-    pos += 4;
-    let program_start = pos + header_length;
+    // address_size/segment_selector_size were inserted right after `version`
+    // in DWARF5; segmented addressing isn't something WASM targets emit, so
+    // bail rather than silently drop the segment selector from addresses.
+    // v2-v4 carry no address_size field here, so fall back to the unit's
+    // 32/64-bit DWARF format as a proxy for its target address width (a
+    // memory64 WASM module's `.debug_line` is emitted 64-bit DWARF).
+    let address_size: u8 = if version >= 5 {
+        let a = read_u8(data, pos)?;
+        pos += 1;
+        let segment_selector_size = read_u8(data, pos)?;
+        pos += 1;
+        if segment_selector_size != 0 {
+            return None;
+        }
+        a
+    } else if is_64bit {
+        8
+    } else {
+        4
+    };
+
+    // header_length (4 bytes for DWARF32, 8 for DWARF64)
+    let (header_length, n) = read_offset(data, pos, is_64bit)?;
+    pos += n;
+    let program_start = pos + header_length as usize;
 
     let minimum_instruction_length = read_u8(data, pos)?;
     pos += 1;
@@ -230,51 +625,102 @@ fn parse_debug_line(data: &[u8], target_addr: u64) -> Option<SourceLocation> {
     let standard_opcode_lengths: Vec<u8> = data[pos..pos + std_opcodes_count].to_vec();
     pos += std_opcodes_count;
 
-    // include_directories: null-terminated strings, list terminated by empty string
-    let mut include_dirs: Vec<String> = vec![String::new()]; // index 0 = compilation directory
-    loop {
-        if pos >= data.len() {
-            return None;
-        }
-        if data[pos] == 0 {
-            pos += 1; // terminator
-            break;
+    let (include_dirs, file_names): (Vec<String>, Vec<(String, usize)>) = if version >= 5 {
+        let dir_formats = read_line_entry_format_table(data, &mut pos)?;
+        let (dirs_count, n) = read_uleb128(data, pos)?;
+        pos += n;
+        let mut include_dirs = Vec::with_capacity(dirs_count as usize);
+        for _ in 0..dirs_count {
+            let mut path = String::new();
+            for &(content_type, form) in &dir_formats {
+                let (value, n) = read_line_entry_field(data, pos, form, debug_line_str, debug_str, is_64bit)?;
+                pos += n;
+                if content_type == DW_LNCT_PATH {
+                    if let LineEntryValue::Str(s) = value {
+                        path = s;
+                    }
+                }
+            }
+            include_dirs.push(path);
         }
-        let (s, n) = read_cstr(data, pos)?;
-        include_dirs.push(s);
+
+        let file_formats = read_line_entry_format_table(data, &mut pos)?;
+        let (files_count, n) = read_uleb128(data, pos)?;
         pos += n;
-    }
+        let mut file_names = Vec::with_capacity(files_count as usize);
+        for _ in 0..files_count {
+            let mut name = String::new();
+            let mut dir_idx = 0usize;
+            for &(content_type, form) in &file_formats {
+                let (value, n) = read_line_entry_field(data, pos, form, debug_line_str, debug_str, is_64bit)?;
+                pos += n;
+                match (content_type, value) {
+                    (DW_LNCT_PATH, LineEntryValue::Str(s)) => name = s,
+                    (DW_LNCT_DIRECTORY_INDEX, LineEntryValue::Uint(v)) => dir_idx = v as usize,
+                    _ => {}
+                }
+            }
+            file_names.push((name, dir_idx));
+        }
 
-    // file_names: (name, dir_index, last_modified, file_length) per entry; list terminated by 0x00
-    let mut file_names: Vec<(String, usize)> = vec![(String::new(), 0)]; // index 0 unused per spec
-    loop {
-        if pos >= data.len() {
-            return None;
+        (include_dirs, file_names)
+    } else {
+        // include_directories: null-terminated strings, list terminated by empty string
+        let mut include_dirs: Vec<String> = vec![String::new()]; // index 0 = compilation directory
+        loop {
+            if pos >= data.len() {
+                return None;
+            }
+            if data[pos] == 0 {
+                pos += 1; // terminator
+                break;
+            }
+            let (s, n) = read_cstr(data, pos)?;
+            include_dirs.push(s);
+            pos += n;
         }
-        if data[pos] == 0 {
-            // Terminator byte; pos is overridden to program_start below so no need to advance.
-            break;
+
+        // file_names: (name, dir_index, last_modified, file_length) per entry; list terminated by 0x00
+        let mut file_names: Vec<(String, usize)> = vec![(String::new(), 0)]; // index 0 unused per spec
+        loop {
+            if pos >= data.len() {
+                return None;
+            }
+            if data[pos] == 0 {
+                // Terminator byte; pos is overridden to program_start below so no need to advance.
+                break;
+            }
+            let (name, n) = read_cstr(data, pos)?;
+            pos += n;
+            let (dir_idx, n) = read_uleb128(data, pos)?;
+            pos += n;
+            let (_, n) = read_uleb128(data, pos)?; // last_modified
+            pos += n;
+            let (_, n) = read_uleb128(data, pos)?; // file_length
+            pos += n;
+            file_names.push((name, dir_idx as usize));
         }
-        let (name, n) = read_cstr(data, pos)?;
-        pos += n;
-        let (dir_idx, n) = read_uleb128(data, pos)?;
-        pos += n;
-        let (_, n) = read_uleb128(data, pos)?; // last_modified
-        pos += n;
-        let (_, n) = read_uleb128(data, pos)?; // file_length
-        pos += n;
-        file_names.push((name, dir_idx as usize));
-    }
+
+        (include_dirs, file_names)
+    };
 
     // Advance to the line number program
     pos = program_start;
 
     // --- State machine registers ---
     let mut address: u64 = 0;
-    let mut file_idx: usize = 1;
+    // v5 renumbers file indices from 0 (the primary source file); v2-v4
+    // reserve index 0 and start real entries at 1.
+    let default_file_idx: usize = if version >= 5 { 0 } else { 1 };
+    let mut file_idx: usize = default_file_idx;
     let mut line: i64 = 1;
     let mut column: u64 = 0;
 
+    let mut rows: Vec<LineRow> = Vec::new();
+    // Rows emitted by the current sequence, finalized into `rows` (with their
+    // `end_addr` bounds filled in) once its `DW_LNE_end_sequence` is reached.
+    let mut sequence: Vec<(u64, SourceLocation)> = Vec::new();
+
     while pos < unit_end {
         let opcode = read_u8(data, pos)?;
         pos += 1;
@@ -292,19 +738,26 @@ fn parse_debug_line(data: &[u8], target_addr: u64) -> Option<SourceLocation> {
 
             match ext_opcode {
                 1 => {
-                    // DW_LNE_end_sequence -- reset state, do not emit
+                    // DW_LNE_end_sequence -- bounds the last row of the
+                    // sequence at `address`, then resets state for the next one.
+                    finalize_sequence(&mut sequence, address, &mut rows);
                     address = 0;
-                    file_idx = 1;
+                    file_idx = default_file_idx;
                     line = 1;
                     column = 0;
                     pos = ext_end;
                 }
                 2 => {
-                    // DW_LNE_set_address (4-byte address for 32-bit WASM)
-                    if pos + 4 > ext_end {
+                    // DW_LNE_set_address -- 4 bytes for 32-bit WASM, 8 for
+                    // memory64 (address_size resolved above).
+                    if pos + address_size as usize > ext_end {
                         return None;
                     }
-                    address = read_u32_le(data, pos)? as u64;
+                    address = match address_size {
+                        4 => read_u32_le(data, pos)? as u64,
+                        8 => read_u64_le(data, pos)?,
+                        _ => return None,
+                    };
                     pos = ext_end;
                 }
                 _ => {
@@ -316,8 +769,8 @@ fn parse_debug_line(data: &[u8], target_addr: u64) -> Option<SourceLocation> {
             match opcode {
                 1 => {
                     // DW_LNS_copy -- emit a row
-                    if address == target_addr {
-                        return build_location(&file_names, &include_dirs, file_idx, line, column);
+                    if let Some(loc) = build_location(&file_names, &include_dirs, file_idx, line, column, path_remaps) {
+                        sequence.push((address, loc));
                     }
                 }
                 2 => {
@@ -370,13 +823,59 @@ fn parse_debug_line(data: &[u8], target_addr: u64) -> Option<SourceLocation> {
                 / maximum_ops_per_instruction as u64;
             line = line.wrapping_add(line_delta);
 
-            if address == target_addr {
-                return build_location(&file_names, &include_dirs, file_idx, line, column);
+            if let Some(loc) = build_location(&file_names, &include_dirs, file_idx, line, column, path_remaps) {
+                sequence.push((address, loc));
             }
         }
     }
 
-    None
+    // Sorted again (across all units) by the caller once every unit's rows
+    // are merged; no need to sort here too.
+    Some((FileTable { file_names, include_dirs }, rows, unit_end))
+}
+
+/// Turns the `(address, location)` pairs accumulated for one line-program
+/// sequence into bounded `LineRow`s and appends them to `rows`. Each row's
+/// `end_addr` is the next row's address, or `sequence_end` (the address
+/// carried by that sequence's `DW_LNE_end_sequence`) for the last one.
+fn finalize_sequence(sequence: &mut Vec<(u64, SourceLocation)>, sequence_end: u64, rows: &mut Vec<LineRow>) {
+    for i in 0..sequence.len() {
+        let (start_addr, location) = sequence[i].clone();
+        let end_addr = sequence.get(i + 1).map_or(sequence_end, |(addr, _)| *addr);
+        rows.push(LineRow { start_addr, end_addr, location });
+    }
+    sequence.clear();
+}
+
+/// Looks up `target_addr` in a sorted `line_table` built by
+/// [`build_line_table`], returning the location of the row whose
+/// `[start_addr, end_addr)` range contains it.
+fn lookup_line_table(line_table: &[LineRow], target_addr: u64) -> Option<SourceLocation> {
+    let idx = match line_table.binary_search_by(|row| row.start_addr.cmp(&target_addr)) {
+        Ok(idx) => idx,
+        Err(0) => return None,
+        Err(idx) => idx - 1,
+    };
+    let row = &line_table[idx];
+    if target_addr < row.end_addr {
+        Some(row.location.clone())
+    } else {
+        None
+    }
+}
+
+/// Demangles `name` with `rustc_demangle` if it starts with a Rust legacy
+/// (`_ZN`, or `__Z`) or v0 (`_R`) prefix; otherwise returns it unchanged.
+/// `rustc_demangle` decodes Rust mangling only, so an Itanium C++ symbol is
+/// not turned into its C++ surface form. Symbolizers commonly
+/// need this because `DW_AT_name` on WASM-targeting rustc output is the
+/// mangled link name, not the surface-level function name.
+fn demangle_symbol(name: &str) -> String {
+    if name.starts_with("_ZN") || name.starts_with("_R") || name.starts_with("__Z") {
+        rustc_demangle::demangle(name).to_string()
+    } else {
+        name.to_string()
+    }
 }
 
 fn build_location(
@@ -385,6 +884,7 @@ fn build_location(
     file_idx: usize,
     line: i64,
     column: u64,
+    path_remaps: &[(String, String)],
 ) -> Option<SourceLocation> {
     let (file_name, dir_idx) = file_names.get(file_idx)?;
     let dir = include_dirs.get(*dir_idx).map(String::as_str).unwrap_or("");
@@ -394,13 +894,27 @@ fn build_location(
         format!("{}/{}", dir, file_name)
     };
     Some(SourceLocation {
-        file: full_path,
+        file: remap_path(&full_path, path_remaps),
         line: line.max(0) as u32,
-        column: if column > 0 { column as u32 } else { 0 },
+        column: (column > 0).then_some(column as u32),
         column_end: None,
+        function: None,
     })
 }
 
+/// Replaces the first `from_prefix` in `path_remaps` that `path` starts with,
+/// with its paired `to_prefix`. `path_remaps` is checked in order, so an
+/// earlier, more specific prefix wins over a later, broader one. Returns
+/// `path` unchanged when nothing matches.
+fn remap_path(path: &str, path_remaps: &[(String, String)]) -> String {
+    for (from_prefix, to_prefix) in path_remaps {
+        if let Some(rest) = path.strip_prefix(from_prefix.as_str()) {
+            return format!("{}{}", to_prefix, rest);
+        }
+    }
+    path.to_string()
+}
+
 // --- Byte-level helpers (no external dependencies) ---
 
 fn read_u8(data: &[u8], pos: usize) -> Option<u8> {
@@ -421,12 +935,127 @@ fn read_u32_le(data: &[u8], pos: usize) -> Option<u32> {
     Some(u32::from_le_bytes(bytes))
 }
 
+fn read_u64_le(data: &[u8], pos: usize) -> Option<u64> {
+    let bytes: [u8; 8] = data.get(pos..pos + 8)?.try_into().ok()?;
+    Some(u64::from_le_bytes(bytes))
+}
+
+/// Reads a DWARF "initial length"-sized field: 4 bytes for 32-bit DWARF, or
+/// 8 bytes for 64-bit DWARF (the format `header_length` and section-offset
+/// forms like `DW_FORM_strp`/`DW_FORM_line_strp` also follow once a unit has
+/// signalled 64-bit DWARF via the `0xffffffff` initial-length escape).
+fn read_offset(data: &[u8], pos: usize, is_64bit: bool) -> Option<(u64, usize)> {
+    if is_64bit {
+        Some((read_u64_le(data, pos)?, 8))
+    } else {
+        Some((read_u32_le(data, pos)? as u64, 4))
+    }
+}
+
 fn read_cstr(data: &[u8], pos: usize) -> Option<(String, usize)> {
     let end = data[pos..].iter().position(|&b| b == 0)?;
     let s = std::str::from_utf8(&data[pos..pos + end]).ok()?.to_string();
     Some((s, end + 1)) // +1 for the null terminator
 }
 
+/// Resolves a `DW_FORM_strp`/`DW_FORM_line_strp` offset against the given
+/// `.debug_str`/`.debug_line_str` buffer, reading the NUL-terminated string
+/// at that offset. Returns `None` when the section is absent or the offset
+/// is out of range, letting callers fall back to an empty/placeholder name
+/// instead of failing the whole lookup.
+fn resolve_strp(section: Option<&[u8]>, offset: u64) -> Option<String> {
+    let section = section?;
+    let (s, _) = read_cstr(section, usize::try_from(offset).ok()?)?;
+    Some(s)
+}
+
+// DWARF5 line-number content type codes (`DW_LNCT_*`) used by the
+// entry-format-described `directories`/`file_names` tables below.
+const DW_LNCT_PATH: u64 = 0x1;
+const DW_LNCT_DIRECTORY_INDEX: u64 = 0x2;
+
+const DW_FORM_STRING: u64 = 0x08;
+const DW_FORM_DATA1: u64 = 0x0b;
+const DW_FORM_DATA2: u64 = 0x05;
+const DW_FORM_DATA4: u64 = 0x06;
+const DW_FORM_DATA8: u64 = 0x07;
+const DW_FORM_DATA16: u64 = 0x1e;
+const DW_FORM_STRP: u64 = 0x0e;
+const DW_FORM_UDATA: u64 = 0x0f;
+const DW_FORM_LINE_STRP: u64 = 0x1f;
+const DW_FORM_BLOCK: u64 = 0x09;
+
+/// A decoded field value from a v5 directory/file-name table entry: either
+/// the path-shaped forms (`DW_FORM_string`/`DW_FORM_strp`/`DW_FORM_line_strp`)
+/// resolve to `Str`, `DW_LNCT_directory_index`'s `DW_FORM_udata`/`DW_FORM_dataN`
+/// resolve to `Uint`, and everything else (timestamp, size, MD5 checksum) is
+/// consumed as `Bytes` since this parser has no use for those fields.
+enum LineEntryValue {
+    Str(String),
+    Uint(u64),
+    Bytes,
+}
+
+/// Reads the `directory_entry_format`/`file_name_entry_format` table that
+/// precedes a v5 directories/file_names list: a count byte followed by that
+/// many `(content_type_code, form)` ULEB128 pairs.
+fn read_line_entry_format_table(data: &[u8], pos: &mut usize) -> Option<Vec<(u64, u64)>> {
+    let format_count = read_u8(data, *pos)?;
+    *pos += 1;
+    let mut formats = Vec::with_capacity(format_count as usize);
+    for _ in 0..format_count {
+        let (content_type, n) = read_uleb128(data, *pos)?;
+        *pos += n;
+        let (form, n) = read_uleb128(data, *pos)?;
+        *pos += n;
+        formats.push((content_type, form));
+    }
+    Some(formats)
+}
+
+/// Reads a single `(content_type_code, form)`-described field value for a v5
+/// directory/file-name table entry, resolving `DW_FORM_strp`/`DW_FORM_line_strp`
+/// offsets against `debug_str`/`debug_line_str`. Returns the decoded value
+/// plus how many bytes it consumed.
+fn read_line_entry_field(
+    data: &[u8],
+    pos: usize,
+    form: u64,
+    debug_line_str: Option<&[u8]>,
+    debug_str: Option<&[u8]>,
+    is_64bit: bool,
+) -> Option<(LineEntryValue, usize)> {
+    match form {
+        DW_FORM_STRING => {
+            let (s, n) = read_cstr(data, pos)?;
+            Some((LineEntryValue::Str(s), n))
+        }
+        DW_FORM_LINE_STRP => {
+            let (offset, n) = read_offset(data, pos, is_64bit)?;
+            Some((LineEntryValue::Str(resolve_strp(debug_line_str, offset).unwrap_or_default()), n))
+        }
+        DW_FORM_STRP => {
+            let (offset, n) = read_offset(data, pos, is_64bit)?;
+            Some((LineEntryValue::Str(resolve_strp(debug_str, offset).unwrap_or_default()), n))
+        }
+        DW_FORM_UDATA => {
+            let (v, n) = read_uleb128(data, pos)?;
+            Some((LineEntryValue::Uint(v), n))
+        }
+        DW_FORM_DATA1 => Some((LineEntryValue::Uint(read_u8(data, pos)? as u64), 1)),
+        DW_FORM_DATA2 => Some((LineEntryValue::Uint(read_u16_le(data, pos)? as u64), 2)),
+        DW_FORM_DATA4 => Some((LineEntryValue::Uint(read_u32_le(data, pos)? as u64), 4)),
+        DW_FORM_DATA8 => (pos + 8 <= data.len()).then_some((LineEntryValue::Bytes, 8)),
+        DW_FORM_DATA16 => (pos + 16 <= data.len()).then_some((LineEntryValue::Bytes, 16)),
+        DW_FORM_BLOCK => {
+            let (len, n) = read_uleb128(data, pos)?;
+            let total = n + len as usize;
+            (pos + total <= data.len()).then_some((LineEntryValue::Bytes, total))
+        }
+        _ => None, // unsupported form in an entry-format table
+    }
+}
+
 fn read_uleb128(data: &[u8], pos: usize) -> Option<(u64, usize)> {
     let mut result: u64 = 0;
     let mut shift = 0u32;
@@ -470,6 +1099,466 @@ fn read_sleb128(data: &[u8], pos: usize) -> Option<(i64, usize)> {
     Some((result, consumed))
 }
 
+/// Minimal `.debug_info`/`.debug_abbrev` DIE-tree walker used to resolve the
+/// inline call stack and the function name enclosing a WASM
+/// offset. Deliberately hand-rolled rather than pulled in via `gimli` to stay
+/// consistent with the rest of this file's dependency-free DWARF parsing.
+///
+/// Only the single leading compilation unit is parsed and only DWARF32 is
+/// supported -- see the `TODO: iterate all CUs` above
+/// [`SourceMapper::map_wasm_offset_to_source`], which tracks extending this
+/// walker the way `build_line_table` now handles multiple `.debug_line` units.
+mod dwarf {
+    use super::{read_sleb128, read_u16_le, read_u32_le, read_u8, read_uleb128, remap_path, FileTable};
+    use crate::source_mapper::SourceLocation;
+
+    const DW_TAG_COMPILE_UNIT: u64 = 0x11;
+    const DW_TAG_SUBPROGRAM: u64 = 0x2e;
+    const DW_TAG_INLINED_SUBROUTINE: u64 = 0x1d;
+
+    const DW_AT_LOW_PC: u64 = 0x11;
+    const DW_AT_HIGH_PC: u64 = 0x12;
+    const DW_AT_NAME: u64 = 0x03;
+    const DW_AT_ABSTRACT_ORIGIN: u64 = 0x31;
+    const DW_AT_SPECIFICATION: u64 = 0x47;
+    const DW_AT_CALL_FILE: u64 = 0x58;
+    const DW_AT_CALL_LINE: u64 = 0x59;
+
+    const DW_FORM_ADDR: u64 = 0x01;
+    const DW_FORM_BLOCK2: u64 = 0x03;
+    const DW_FORM_BLOCK4: u64 = 0x04;
+    const DW_FORM_DATA2: u64 = 0x05;
+    const DW_FORM_DATA4: u64 = 0x06;
+    const DW_FORM_DATA8: u64 = 0x07;
+    const DW_FORM_STRING: u64 = 0x08;
+    const DW_FORM_BLOCK: u64 = 0x09;
+    const DW_FORM_BLOCK1: u64 = 0x0a;
+    const DW_FORM_DATA1: u64 = 0x0b;
+    const DW_FORM_FLAG: u64 = 0x0c;
+    const DW_FORM_SDATA: u64 = 0x0d;
+    const DW_FORM_STRP: u64 = 0x0e;
+    const DW_FORM_UDATA: u64 = 0x0f;
+    const DW_FORM_REF_ADDR: u64 = 0x10;
+    const DW_FORM_REF1: u64 = 0x11;
+    const DW_FORM_REF2: u64 = 0x12;
+    const DW_FORM_REF4: u64 = 0x13;
+    const DW_FORM_REF8: u64 = 0x14;
+    const DW_FORM_REF_UDATA: u64 = 0x15;
+    const DW_FORM_INDIRECT: u64 = 0x16;
+    const DW_FORM_SEC_OFFSET: u64 = 0x17;
+    const DW_FORM_EXPRLOC: u64 = 0x18;
+    const DW_FORM_FLAG_PRESENT: u64 = 0x19;
+    const DW_FORM_REF_SIG8: u64 = 0x20;
+
+    /// A decoded attribute value, narrowed to what the inline-frame walker
+    /// and function-name resolution actually need.
+    #[derive(Debug, Clone)]
+    pub(super) enum AttrValue {
+        Addr(u64),
+        Const(u64),
+        Str(String),
+        /// Offset into `.debug_str`/`.debug_line_str` (unresolved for now).
+        StrOffset(u64),
+        /// Offset of another DIE within `.debug_info`, from `DW_FORM_ref4` or
+        /// `DW_FORM_ref_udata`; the other reference forms decode as `Const`.
+        Ref(u64),
+        Flag(bool),
+    }
+
+    struct Abbrev {
+        tag: u64,
+        has_children: bool,
+        attrs: Vec<(u64, u64)>, // (attribute, form)
+    }
+
+    /// Parses `.debug_abbrev` into a code -> Abbrev table for the single
+    /// abbreviation table starting at offset 0 (the only one a single-CU
+    /// producer emits).
+    fn parse_abbrev_table(data: &[u8]) -> Option<std::collections::HashMap<u64, Abbrev>> {
+        let mut table = std::collections::HashMap::new();
+        let mut pos = 0usize;
+
+        while pos < data.len() {
+            let (code, n) = read_uleb128(data, pos)?;
+            pos += n;
+            if code == 0 {
+                break; // end of table
+            }
+
+            let (tag, n) = read_uleb128(data, pos)?;
+            pos += n;
+
+            let has_children = read_u8(data, pos)? != 0;
+            pos += 1;
+
+            let mut attrs = Vec::new();
+            loop {
+                let (attr, n) = read_uleb128(data, pos)?;
+                pos += n;
+                let (form, n) = read_uleb128(data, pos)?;
+                pos += n;
+                if attr == 0 && form == 0 {
+                    break;
+                }
+                attrs.push((attr, form));
+            }
+
+            table.insert(
+                code,
+                Abbrev {
+                    tag,
+                    has_children,
+                    attrs,
+                },
+            );
+        }
+
+        Some(table)
+    }
+
+    /// Reads a single attribute value for `form`, returning the decoded value
+    /// and the number of bytes consumed. Forms this parser does not need to
+    /// interpret (blocks, exprlocs, signatures) are still consumed correctly
+    /// so the DIE tree walk doesn't desync, they just decode to a placeholder.
+    fn read_form_value(
+        data: &[u8],
+        pos: usize,
+        form: u64,
+        address_size: u8,
+    ) -> Option<(AttrValue, usize)> {
+        match form {
+            DW_FORM_ADDR => match address_size {
+                4 => Some((AttrValue::Addr(read_u32_le(data, pos)? as u64), 4)),
+                8 => {
+                    let bytes: [u8; 8] = data.get(pos..pos + 8)?.try_into().ok()?;
+                    Some((AttrValue::Addr(u64::from_le_bytes(bytes)), 8))
+                }
+                _ => None,
+            },
+            DW_FORM_DATA1 | DW_FORM_REF1 | DW_FORM_FLAG => {
+                Some((AttrValue::Const(read_u8(data, pos)? as u64), 1))
+            }
+            DW_FORM_DATA2 | DW_FORM_REF2 => {
+                Some((AttrValue::Const(read_u16_le(data, pos)? as u64), 2))
+            }
+            DW_FORM_DATA4 | DW_FORM_REF4 | DW_FORM_SEC_OFFSET | DW_FORM_REF_ADDR | DW_FORM_STRP => {
+                let v = read_u32_le(data, pos)? as u64;
+                if form == DW_FORM_REF4 {
+                    Some((AttrValue::Ref(v), 4))
+                } else if form == DW_FORM_STRP {
+                    Some((AttrValue::StrOffset(v), 4))
+                } else {
+                    Some((AttrValue::Const(v), 4))
+                }
+            }
+            DW_FORM_DATA8 | DW_FORM_REF8 | DW_FORM_REF_SIG8 => {
+                let bytes: [u8; 8] = data.get(pos..pos + 8)?.try_into().ok()?;
+                Some((AttrValue::Const(u64::from_le_bytes(bytes)), 8))
+            }
+            DW_FORM_SDATA => {
+                let (v, n) = read_sleb128(data, pos)?;
+                Some((AttrValue::Const(v as u64), n))
+            }
+            DW_FORM_UDATA | DW_FORM_REF_UDATA => {
+                let (v, n) = read_uleb128(data, pos)?;
+                if form == DW_FORM_REF_UDATA {
+                    Some((AttrValue::Ref(v), n))
+                } else {
+                    Some((AttrValue::Const(v), n))
+                }
+            }
+            DW_FORM_STRING => {
+                let (s, n) = super::read_cstr(data, pos)?;
+                Some((AttrValue::Str(s), n))
+            }
+            DW_FORM_FLAG_PRESENT => Some((AttrValue::Flag(true), 0)),
+            DW_FORM_BLOCK1 => {
+                let len = read_u8(data, pos)? as usize;
+                Some((AttrValue::Const(0), 1 + len))
+            }
+            DW_FORM_BLOCK2 => {
+                let len = read_u16_le(data, pos)? as usize;
+                Some((AttrValue::Const(0), 2 + len))
+            }
+            DW_FORM_BLOCK4 => {
+                let len = read_u32_le(data, pos)? as usize;
+                Some((AttrValue::Const(0), 4 + len))
+            }
+            DW_FORM_BLOCK | DW_FORM_EXPRLOC => {
+                let (len, n) = read_uleb128(data, pos)?;
+                Some((AttrValue::Const(0), n + len as usize))
+            }
+            DW_FORM_INDIRECT => {
+                let (real_form, n) = read_uleb128(data, pos)?;
+                let (val, n2) = read_form_value(data, pos + n, real_form, address_size)?;
+                Some((val, n + n2))
+            }
+            _ => None, // unsupported form (e.g. DWARF5 strx/addrx); bail out
+        }
+    }
+
+    /// A DIE together with its resolved attribute map and children, enough to
+    /// answer "which inlined_subroutine chain contains this address".
+    struct Die {
+        tag: u64,
+        offset: usize,
+        attrs: Vec<(u64, AttrValue)>,
+        children: Vec<Die>,
+    }
+
+    impl Die {
+        fn attr(&self, attr: u64) -> Option<&AttrValue> {
+            self.attrs.iter().find(|(a, _)| *a == attr).map(|(_, v)| v)
+        }
+
+        /// Resolves the `[low, high)` PC range of this DIE from
+        /// `DW_AT_low_pc`/`DW_AT_high_pc`, handling the case where
+        /// `high_pc` is encoded as an address-relative offset rather than an
+        /// absolute address (the common case since DWARF4).
+        fn pc_range(&self) -> Option<(u64, u64)> {
+            let low = match self.attr(DW_AT_LOW_PC)? {
+                AttrValue::Addr(a) => *a,
+                AttrValue::Const(c) => *c,
+                _ => return None,
+            };
+            let high = match self.attr(DW_AT_HIGH_PC)? {
+                AttrValue::Addr(a) => *a,
+                AttrValue::Const(offset) => low + *offset,
+                _ => return None,
+            };
+            Some((low, high))
+        }
+
+        fn contains(&self, addr: u64) -> bool {
+            self.pc_range()
+                .is_some_and(|(low, high)| addr >= low && addr < high)
+        }
+
+        fn name(&self) -> Option<String> {
+            match self.attr(DW_AT_NAME) {
+                Some(AttrValue::Str(s)) => Some(s.clone()),
+                _ => None,
+            }
+        }
+    }
+
+    /// Parses the DIE tree of the first compilation unit in `.debug_info`.
+    fn parse_cu_die_tree(info: &[u8], abbrev: &[u8]) -> Option<Die> {
+        let abbrevs = parse_abbrev_table(abbrev)?;
+
+        let mut pos = 0usize;
+        let unit_length = read_u32_le(info, pos)? as usize;
+        if unit_length == 0xffff_ffff {
+            return None; // 64-bit DWARF not supported here
+        }
+        pos += 4;
+        let unit_end = pos + unit_length;
+        if unit_end > info.len() {
+            return None;
+        }
+
+        let version = read_u16_le(info, pos)?;
+        pos += 2;
+
+        let address_size = if version >= 5 {
+            // DWARF5 header: unit_type(1) + address_size(1) + debug_abbrev_offset(4)
+            let _unit_type = read_u8(info, pos)?;
+            pos += 1;
+            let address_size = read_u8(info, pos)?;
+            pos += 1;
+            let _debug_abbrev_offset = read_u32_le(info, pos)?;
+            pos += 4;
+            address_size
+        } else {
+            let _debug_abbrev_offset = read_u32_le(info, pos)?;
+            pos += 4;
+            let address_size = read_u8(info, pos)?;
+            pos += 1;
+            address_size
+        };
+
+        let (root, _) = parse_die(info, pos, unit_end, &abbrevs, address_size)?;
+        Some(root)
+    }
+
+    /// Recursively parses one DIE (and, if it has children, its subtree)
+    /// starting at `pos`, returning the DIE and the position just past it
+    /// (and its children, if any).
+    fn parse_die(
+        data: &[u8],
+        pos: usize,
+        unit_end: usize,
+        abbrevs: &std::collections::HashMap<u64, Abbrev>,
+        address_size: u8,
+    ) -> Option<(Die, usize)> {
+        let die_offset = pos;
+        let (code, n) = read_uleb128(data, pos)?;
+        let mut cursor = pos + n;
+
+        if code == 0 {
+            // Null entry (end-of-children marker); caller handles this.
+            return Some((
+                Die {
+                    tag: 0,
+                    offset: die_offset,
+                    attrs: Vec::new(),
+                    children: Vec::new(),
+                },
+                cursor,
+            ));
+        }
+
+        let abbrev = abbrevs.get(&code)?;
+        let mut attrs = Vec::with_capacity(abbrev.attrs.len());
+        for (attr, form) in &abbrev.attrs {
+            let (value, consumed) = read_form_value(data, cursor, *form, address_size)?;
+            cursor += consumed;
+            attrs.push((*attr, value));
+        }
+
+        let mut children = Vec::new();
+        if abbrev.has_children {
+            loop {
+                if cursor >= unit_end {
+                    break;
+                }
+                let (child, next) = parse_die(data, cursor, unit_end, abbrevs, address_size)?;
+                cursor = next;
+                if child.tag == 0 {
+                    break; // end-of-children marker
+                }
+                children.push(child);
+            }
+        }
+
+        Some((
+            Die {
+                tag: abbrev.tag,
+                offset: die_offset,
+                attrs,
+                children,
+            },
+            cursor,
+        ))
+    }
+
+    /// Result of walking the DIE tree for an address: the innermost frame's
+    /// function name (if any) plus the chain of enclosing inlined frames,
+    /// outermost last.
+    pub(super) struct InlineChain {
+        pub(super) innermost_function: Option<String>,
+        pub(super) enclosing: Vec<SourceLocation>,
+    }
+
+    /// Finds the `DW_TAG_subprogram`/`DW_TAG_inlined_subroutine` chain
+    /// enclosing `addr` and returns the synthesized caller frames.
+    pub(super) fn inline_chain_for_address(
+        info: &[u8],
+        abbrev: &[u8],
+        addr: u64,
+        file_table: &FileTable,
+        path_remaps: &[(String, String)],
+    ) -> Option<InlineChain> {
+        let root = parse_cu_die_tree(info, abbrev)?;
+        let mut path = Vec::new();
+        collect_path(&root, addr, &mut path);
+        if path.is_empty() {
+            return None;
+        }
+
+        // `path` is outermost-first (root to innermost); the innermost
+        // function name comes from the last subprogram/inlined_subroutine on
+        // the path, and each inlined frame above it (outer ones) contributes
+        // a synthesized caller SourceLocation from its own call_file/call_line.
+        let innermost_function = path
+            .last()
+            .and_then(|die| die.name().or_else(|| resolve_origin_name(&root, die)));
+
+        let mut enclosing = Vec::new();
+        for die in path.iter().rev().skip(1) {
+            if die.tag != DW_TAG_INLINED_SUBROUTINE {
+                continue;
+            }
+            let line = match die.attr(DW_AT_CALL_LINE) {
+                Some(AttrValue::Const(l)) => *l as u32,
+                _ => 0,
+            };
+            let file = match die.attr(DW_AT_CALL_FILE) {
+                Some(AttrValue::Const(idx)) => file_table
+                    .path_for(*idx as usize)
+                    .map(|path| remap_path(&path, path_remaps))
+                    .unwrap_or_default(),
+                _ => String::new(),
+            };
+            enclosing.push(SourceLocation {
+                file,
+                line,
+                column: None,
+                column_end: None,
+                function: die.name().or_else(|| resolve_origin_name(&root, die)),
+            });
+        }
+
+        Some(InlineChain {
+            innermost_function,
+            enclosing,
+        })
+    }
+
+    /// Resolves just the name of the innermost function enclosing `addr`,
+    /// without building the full inline chain. Used by
+    /// `SourceMapper::map_wasm_offset_to_source`, which only surfaces a flat
+    /// `function @ file:line` frame.
+    pub(super) fn function_name_for_address(info: &[u8], abbrev: &[u8], addr: u64) -> Option<String> {
+        let root = parse_cu_die_tree(info, abbrev)?;
+        let mut path = Vec::new();
+        collect_path(&root, addr, &mut path);
+        let innermost = path.last()?;
+        innermost.name().or_else(|| resolve_origin_name(&root, innermost))
+    }
+
+    /// Resolves a DIE's name via `DW_AT_abstract_origin`/`DW_AT_specification`
+    /// when the name isn't attached directly (common for inlined/declared
+    /// functions, whose name lives on a separate declaration DIE).
+    fn resolve_origin_name(root: &Die, die: &Die) -> Option<String> {
+        let origin_offset = match die
+            .attr(DW_AT_ABSTRACT_ORIGIN)
+            .or_else(|| die.attr(DW_AT_SPECIFICATION))
+        {
+            Some(AttrValue::Ref(offset)) => *offset,
+            _ => return None,
+        };
+        find_die_by_offset(root, origin_offset as usize).and_then(Die::name)
+    }
+
+    fn find_die_by_offset(die: &Die, offset: usize) -> Option<&Die> {
+        if die.offset == offset {
+            return Some(die);
+        }
+        die.children
+            .iter()
+            .find_map(|child| find_die_by_offset(child, offset))
+    }
+
+    /// Walks `die` looking for the deepest `DW_TAG_subprogram`/
+    /// `DW_TAG_inlined_subroutine` chain whose PC range contains `addr`,
+    /// appending each matching DIE to `path` (outermost first).
+    fn collect_path<'a>(die: &'a Die, addr: u64, path: &mut Vec<&'a Die>) {
+        if die.tag == DW_TAG_SUBPROGRAM || die.tag == DW_TAG_INLINED_SUBROUTINE {
+            if !die.contains(addr) {
+                return;
+            }
+            path.push(die);
+        } else if die.tag != DW_TAG_COMPILE_UNIT {
+            return;
+        }
+
+        for child in &die.children {
+            collect_path(child, addr, path);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -485,6 +1574,36 @@ mod tests {
         assert!(mapper.map_wasm_offset_to_source(0x1234).is_none());
     }
 
+    #[test]
+    fn test_map_wasm_offset_to_frames_without_symbols_is_empty() {
+        let wasm_bytes = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+        let mapper = SourceMapper::new_without_cache(wasm_bytes);
+
+        assert!(mapper.map_wasm_offset_to_frames(0x1234).is_empty());
+    }
+
+    #[test]
+    fn test_map_wasm_offset_to_frames_is_memoized() {
+        let wasm_bytes = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+        let mapper = SourceMapper::new_without_cache(wasm_bytes);
+
+        // Two lookups of the same offset must share one cache entry.
+        mapper.map_wasm_offset_to_frames(0x1234);
+        mapper.map_wasm_offset_to_frames(0x1234);
+        assert_eq!(mapper.frame_cache.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_file_table_path_for_joins_dir_and_file() {
+        let table = FileTable {
+            file_names: vec![(String::new(), 0), ("lib.rs".to_string(), 1)],
+            include_dirs: vec![String::new(), "src".to_string()],
+        };
+
+        assert_eq!(table.path_for(1), Some("src/lib.rs".to_string()));
+        assert_eq!(table.path_for(99), None);
+    }
+
     #[test]
     fn test_source_mapper_with_mock_symbols() {
         // Minimal WASM header only -- no debug sections
@@ -499,8 +1618,9 @@ mod tests {
         let location = SourceLocation {
             file: "test.rs".to_string(),
             line: 42,
-            column: 10,
+            column: Some(10),
             column_end: Some(15),
+            function: None,
         };
 
         let json = serde_json::to_string(&location).unwrap();
@@ -526,6 +1646,192 @@ mod tests {
         assert_eq!(n, 1);
     }
 
+    #[test]
+    fn test_remap_path_replaces_first_matching_prefix() {
+        let remaps = vec![
+            ("/home/alice/project".to_string(), "/src".to_string()),
+            ("/home/alice".to_string(), "/fallback".to_string()),
+        ];
+        assert_eq!(
+            remap_path("/home/alice/project/src/lib.rs", &remaps),
+            "/src/src/lib.rs"
+        );
+        assert_eq!(
+            remap_path("/home/alice/other/lib.rs", &remaps),
+            "/fallback/other/lib.rs"
+        );
+        assert_eq!(remap_path("/unrelated/lib.rs", &remaps), "/unrelated/lib.rs");
+    }
+
+    #[test]
+    fn test_build_line_table_inner_parses_dwarf5_header() {
+        // Header fields between `header_length` and the line number program,
+        // per DWARF5 6.2.4: one directory ("") and one file ("src/test.rs",
+        // dir_index 0), each described by its own entry-format table.
+        let mut header_body = vec![
+            1u8,   // minimum_instruction_length
+            1u8,   // maximum_ops_per_instruction
+            1u8,   // default_is_stmt
+            0xfbu8, // line_base = -5
+            14u8,  // line_range
+            13u8,  // opcode_base
+        ];
+        header_body.extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]); // standard_opcode_lengths
+
+        header_body.push(1); // directory_entry_format_count
+        header_body.push(1); // DW_LNCT_path
+        header_body.push(0x08); // DW_FORM_string
+        header_body.push(1); // directories_count
+        header_body.push(0); // directories[0] = "" (empty cstr)
+
+        header_body.push(2); // file_name_entry_format_count
+        header_body.push(1); // DW_LNCT_path
+        header_body.push(0x08); // DW_FORM_string
+        header_body.push(2); // DW_LNCT_directory_index
+        header_body.push(0x0f); // DW_FORM_udata
+        header_body.push(1); // file_names_count
+        header_body.extend_from_slice(b"src/test.rs\0");
+        header_body.push(0); // file_names[0].directory_index
+
+        // Line number program: set_address(0x1000), advance_line(+41),
+        // set_column(9), copy, advance_pc(+4), end_sequence -- resolves
+        // 0x1000..0x1004 to src/test.rs:42:9 (advance_pc before
+        // end_sequence keeps the row's range non-empty).
+        let mut program = vec![0x00, 0x05, 0x02];
+        program.extend_from_slice(&0x1000u32.to_le_bytes());
+        program.push(3); // DW_LNS_advance_line
+        program.push(41); // sleb128(+41)
+        program.push(5); // DW_LNS_set_column
+        program.push(9); // uleb128(9)
+        program.push(1); // DW_LNS_copy
+        program.push(2); // DW_LNS_advance_pc
+        program.push(4); // uleb128(4)
+        program.extend_from_slice(&[0x00, 0x01, 0x01]); // DW_LNE_end_sequence
+
+        let mut unit_body = Vec::new();
+        unit_body.extend_from_slice(&5u16.to_le_bytes()); // version
+        unit_body.push(4); // address_size
+        unit_body.push(0); // segment_selector_size
+        unit_body.extend_from_slice(&(header_body.len() as u32).to_le_bytes()); // header_length
+        unit_body.extend_from_slice(&header_body);
+        unit_body.extend_from_slice(&program);
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&(unit_body.len() as u32).to_le_bytes()); // unit_length
+        data.extend_from_slice(&unit_body);
+
+        let (file_table, rows) =
+            build_line_table_inner(&data, None, None, &[]).expect("must parse a v5 line program header");
+
+        // v5's file index is 0-based, unlike v2-v4's reserved index 0.
+        assert_eq!(file_table.path_for(0), Some("src/test.rs".to_string()));
+
+        let loc = lookup_line_table(&rows, 0x1000).expect("row must cover 0x1000");
+        assert_eq!(loc.file, "src/test.rs");
+        assert_eq!(loc.line, 42);
+        assert_eq!(loc.column, Some(9));
+    }
+
+    /// Builds one complete DWARF32 v4 `.debug_line` unit (including its own
+    /// `unit_length` prefix) mapping `address` to `(file_name, line)`, for
+    /// concatenating several back-to-back units in a single `data` buffer.
+    fn build_v4_line_unit(address: u32, file_name: &str, line: i64) -> Vec<u8> {
+        let mut header_body = vec![
+            1u8,    // minimum_instruction_length
+            1u8,    // maximum_ops_per_instruction
+            1u8,    // default_is_stmt
+            0xfbu8, // line_base = -5
+            14u8,   // line_range
+            13u8,   // opcode_base
+        ];
+        header_body.extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]); // standard_opcode_lengths
+        header_body.push(0); // include_directories terminator (no extra dirs)
+        header_body.extend_from_slice(file_name.as_bytes());
+        header_body.push(0); // file name terminator
+        header_body.push(0); // directory_index
+        header_body.push(0); // last_modified
+        header_body.push(0); // file_length
+        header_body.push(0); // file_names terminator
+
+        let mut program = vec![0x00, 0x05, 0x02]; // DW_LNE_set_address (4-byte)
+        program.extend_from_slice(&address.to_le_bytes());
+        program.push(3); // DW_LNS_advance_line
+        program.push((line - 1) as u8); // sleb128(line - 1), default line register is 1
+        program.push(1); // DW_LNS_copy
+        program.push(2); // DW_LNS_advance_pc
+        program.push(4); // uleb128(4) -- keeps the row's range non-empty
+        program.extend_from_slice(&[0x00, 0x01, 0x01]); // DW_LNE_end_sequence
+
+        let mut unit_body = Vec::new();
+        unit_body.extend_from_slice(&4u16.to_le_bytes()); // version
+        unit_body.extend_from_slice(&(header_body.len() as u32).to_le_bytes()); // header_length
+        unit_body.extend_from_slice(&header_body);
+        unit_body.extend_from_slice(&program);
+
+        let mut unit = Vec::new();
+        unit.extend_from_slice(&(unit_body.len() as u32).to_le_bytes()); // unit_length
+        unit.extend_from_slice(&unit_body);
+        unit
+    }
+
+    #[test]
+    fn test_build_line_table_inner_merges_rows_across_multiple_units() {
+        // Two back-to-back CUs with out-of-order addresses, proving rows are
+        // merged from every unit and globally re-sorted, not just per-unit.
+        let mut data = build_v4_line_unit(0x2000, "a.rs", 10);
+        data.extend(build_v4_line_unit(0x1000, "b.rs", 20));
+
+        let (_file_table, rows) =
+            build_line_table_inner(&data, None, None, &[]).expect("must parse both compilation units");
+
+        assert_eq!(rows.len(), 2, "rows from both compilation units must be merged");
+        assert_eq!(rows[0].start_addr, 0x1000, "merged rows must be sorted across units");
+        assert_eq!(rows[0].location.file, "b.rs");
+        assert_eq!(rows[1].start_addr, 0x2000);
+        assert_eq!(rows[1].location.file, "a.rs");
+    }
+
+    #[test]
+    fn test_build_line_table_inner_parses_64bit_dwarf_unit() {
+        let mut header_body = vec![1u8, 1, 1, 0xfbu8, 14, 13];
+        header_body.extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]);
+        header_body.push(0); // include_directories terminator
+        header_body.extend_from_slice(b"c.rs\0");
+        header_body.push(0); // directory_index
+        header_body.push(0); // last_modified
+        header_body.push(0); // file_length
+        header_body.push(0); // file_names terminator
+
+        // An address beyond the 32-bit range, exercising memory64 addressing.
+        let address: u64 = 0x1_0000_0000;
+        let mut program = vec![0x00, 0x09, 0x02]; // ext_len = 1 (opcode) + 8 (address)
+        program.extend_from_slice(&address.to_le_bytes());
+        program.push(3); // DW_LNS_advance_line
+        program.push(9); // sleb128(+9) -> line 10
+        program.push(1); // DW_LNS_copy
+        program.push(2); // DW_LNS_advance_pc
+        program.push(4); // uleb128(4)
+        program.extend_from_slice(&[0x00, 0x01, 0x01]); // DW_LNE_end_sequence
+
+        let mut unit_body = Vec::new();
+        unit_body.extend_from_slice(&4u16.to_le_bytes()); // version
+        unit_body.extend_from_slice(&(header_body.len() as u64).to_le_bytes()); // header_length (8 bytes, DWARF64)
+        unit_body.extend_from_slice(&header_body);
+        unit_body.extend_from_slice(&program);
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&0xffff_ffffu32.to_le_bytes()); // 64-bit DWARF escape
+        data.extend_from_slice(&(unit_body.len() as u64).to_le_bytes()); // unit_length
+        data.extend_from_slice(&unit_body);
+
+        let (_file_table, rows) =
+            build_line_table_inner(&data, None, None, &[]).expect("must parse a 64-bit DWARF unit");
+
+        let loc = lookup_line_table(&rows, address).expect("row must cover the 64-bit address");
+        assert_eq!(loc.file, "c.rs");
+        assert_eq!(loc.line, 10);
+    }
+
     #[test]
     fn test_source_mapper_with_cache() {
         let temp_dir = TempDir::new().unwrap();
@@ -554,8 +1860,9 @@ mod tests {
             SourceLocation {
                 file: "test.rs".to_string(),
                 line: 42,
-                column: 10,
+                column: Some(10),
                 column_end: None,
+                function: None,
             },
         );
 
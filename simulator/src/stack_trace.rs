@@ -0,0 +1,77 @@
+// Copyright 2025 Erst Users
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resolves a failed invocation's Wasm call stack into source locations via
+//! [`crate::source_mapper::SourceMapper`], for
+//! [`crate::types::SimulationResponse::stack_trace`]. A plain
+//! `wasm_offset` tells a caller *where in the binary* execution trapped;
+//! this additionally resolves every offset of a caller-supplied call stack
+//! so a caller with debug symbols can see *how it got there*.
+
+use crate::source_mapper::{SourceLocation, SourceMapper};
+use serde::Serialize;
+
+/// One frame of a resolved call stack, innermost first.
+#[derive(Debug, Clone, Serialize)]
+pub struct WasmStackFrame {
+    pub wasm_offset: u64,
+    pub source_location: Option<SourceLocation>,
+}
+
+/// A full resolved call stack for one trap/abort.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct WasmStackTrace {
+    /// Innermost frame first (where execution actually stopped), outermost last.
+    pub frames: Vec<WasmStackFrame>,
+}
+
+impl WasmStackTrace {
+    /// Resolves each offset in `wasm_offsets` (innermost first, as a Wasm
+    /// trap backtrace is usually reported) against `mapper`, leaving
+    /// `source_location` as `None` for any offset the debug info doesn't
+    /// cover rather than dropping the frame.
+    pub fn resolve(mapper: &SourceMapper, wasm_offsets: &[u64]) -> Self {
+        let frames = wasm_offsets
+            .iter()
+            .map(|&wasm_offset| WasmStackFrame {
+                wasm_offset,
+                source_location: mapper.map_wasm_offset_to_source(wasm_offset),
+            })
+            .collect();
+        Self { frames }
+    }
+
+    /// The trap site itself -- the innermost frame's source location, when resolvable.
+    pub fn top_source_location(&self) -> Option<&SourceLocation> {
+        self.frames.first().and_then(|frame| frame.source_location.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_without_debug_symbols_yields_none_locations() {
+        // An empty module has no `.debug_line`, so every offset resolves to `None`
+        // rather than erroring -- a stack trace is still useful for its offsets alone.
+        let mapper = SourceMapper::new(vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00]);
+        let trace = WasmStackTrace::resolve(&mapper, &[10, 20]);
+        assert_eq!(trace.frames.len(), 2);
+        assert!(trace.frames.iter().all(|f| f.source_location.is_none()));
+    }
+
+    #[test]
+    fn test_resolve_preserves_offset_order() {
+        let mapper = SourceMapper::new(vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00]);
+        let trace = WasmStackTrace::resolve(&mapper, &[5, 15, 25]);
+        let offsets: Vec<u64> = trace.frames.iter().map(|f| f.wasm_offset).collect();
+        assert_eq!(offsets, vec![5, 15, 25]);
+    }
+
+    #[test]
+    fn test_top_source_location_is_none_on_empty_trace() {
+        let trace = WasmStackTrace::default();
+        assert!(trace.top_source_location().is_none());
+    }
+}
@@ -3,7 +3,9 @@
 
 #![allow(dead_code)]
 
+use crate::data_source::DataSourceConfig;
 use crate::gas_optimizer::OptimizationReport;
+use crate::manifest::Manifest;
 use crate::stack_trace::WasmStackTrace;
 use crate::source_mapper::SourceLocation;
 use serde::{Deserialize, Serialize};
@@ -24,7 +26,8 @@ pub struct SimulationRequest {
     // Local wasm loading support
     pub wasm_path: Option<String>,
 
-    pub enable_optimization_advisor: bool,
+    #[serde(default)]
+    pub enable_optimization_advisor: Option<bool>,
     pub profile: Option<bool>,
 
     /// RFC 3339 timestamp supplied by caller (reserved for future use)
@@ -34,9 +37,51 @@ pub struct SimulationRequest {
     pub mock_base_fee: Option<u32>,
     pub mock_gas_price: Option<u64>,
 
+    /// Soroban RPC endpoint, filled from the selected `environment`'s
+    /// manifest section when left unset.
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+
+    pub resource_calibration: Option<ResourceCalibration>,
+
     // Optional simulator restore preamble
     #[serde(default)]
     pub restore_preamble: Option<serde_json::Value>,
+
+    /// Which [`crate::data_source::LedgerSource`] resolves `ledger_entries`,
+    /// `contract_wasm`, and `restore_preamble` entries this request didn't
+    /// supply inline. Defaults to [`DataSourceConfig::Inline`], so a missing
+    /// entry stays missing rather than triggering a fetch -- set this to
+    /// `DataSourceConfig::Rpc` to have it fetched (with retry/backoff)
+    /// instead.
+    #[serde(default)]
+    pub data_source: DataSourceConfig,
+
+    /// Name of the [`Manifest`] section (e.g. `"testnet"`, `"mainnet"`,
+    /// `"local"`) [`SimulationRequest::apply_environment`] should fill this
+    /// request's unset fields from. `None` leaves every field as supplied.
+    #[serde(default)]
+    pub environment: Option<String>,
+}
+
+impl SimulationRequest {
+    /// Fills every field this request left `None` from `manifest`'s section
+    /// named by `self.environment`. Lets calibration and mock-fee values
+    /// live once in a checked-in manifest file instead of being repeated on
+    /// every call. A no-op if `self.environment` is `None`.
+    pub fn apply_environment(&mut self, manifest: &Manifest) {
+        let Some(name) = self.environment.as_deref() else {
+            return;
+        };
+        let config = manifest.resolve(name);
+
+        self.resource_calibration = self.resource_calibration.take().or(config.resource_calibration);
+        self.mock_base_fee = self.mock_base_fee.or(config.mock_base_fee);
+        self.mock_gas_price = self.mock_gas_price.or(config.mock_gas_price);
+        self.rpc_url = self.rpc_url.take().or(config.rpc_url);
+        self.enable_optimization_advisor = self.enable_optimization_advisor.or(config.enable_optimization_advisor);
+        self.profile = self.profile.or(config.profile);
+    }
 }
 
 //
@@ -52,6 +97,143 @@ pub struct ResourceCalibration {
     pub ed25519_fixed: u64,
 }
 
+/// The host crypto operations [`ResourceCalibration`] models a cost for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoOp {
+    Sha256,
+    Keccak256,
+    Ed25519Verify,
+}
+
+/// One measurement fed into [`ResourceCalibration::from_measurements`]: the
+/// host budget's reported CPU cost for running `op` over `size_bytes` of
+/// input (size is irrelevant for `Ed25519Verify`, whose cost doesn't vary
+/// with message length the way a hash's does).
+#[derive(Debug, Clone, Copy)]
+pub struct CostSample {
+    pub op: CryptoOp,
+    pub size_bytes: u64,
+    pub cpu_cost: u64,
+}
+
+impl ResourceCalibration {
+    /// Derives calibration coefficients from empirically measured costs
+    /// instead of the hard-coded defaults, so they track the host's actual
+    /// cost model as it drifts across protocol versions. `sha256_fixed`/
+    /// `sha256_per_byte` and `keccak256_fixed`/`keccak256_per_byte` are
+    /// fitted via ordinary least squares over each op's `(size_bytes,
+    /// cpu_cost)` samples; `ed25519_fixed` is the plain average of
+    /// `Ed25519Verify` samples, which carry no size variance to fit against.
+    pub fn from_measurements(samples: &[CostSample]) -> Self {
+        let (sha256_fixed, sha256_per_byte) = fit_linear_cost(samples, CryptoOp::Sha256);
+        let (keccak256_fixed, keccak256_per_byte) = fit_linear_cost(samples, CryptoOp::Keccak256);
+        let ed25519_fixed = average_cost(samples, CryptoOp::Ed25519Verify);
+
+        Self { sha256_fixed, sha256_per_byte, keccak256_fixed, keccak256_per_byte, ed25519_fixed }
+    }
+}
+
+/// Fits `cost = fixed + per_byte * size` over `op`'s samples via ordinary
+/// least squares: `per_byte = (n·Σ(s·c) − Σs·Σc) / (n·Σs² − (Σs)²)`, then
+/// `fixed = (Σc − per_byte·Σs) / n`.
+///
+/// Fewer than two *distinct* sizes leaves the system underdetermined (a line
+/// can pivot freely around a single known point, or there's nothing to fit
+/// at all), so that case falls back to `per_byte = 0` and `fixed` as the
+/// plain average of whatever was measured.
+fn fit_linear_cost(samples: &[CostSample], op: CryptoOp) -> (u64, u64) {
+    let points: Vec<(f64, f64)> =
+        samples.iter().filter(|s| s.op == op).map(|s| (s.size_bytes as f64, s.cpu_cost as f64)).collect();
+
+    let distinct_sizes: std::collections::HashSet<u64> =
+        points.iter().map(|&(size, _)| size as u64).collect();
+    if distinct_sizes.len() < 2 {
+        return (average_cost(samples, op), 0);
+    }
+
+    let n = points.len() as f64;
+    let sum_s: f64 = points.iter().map(|&(s, _)| s).sum();
+    let sum_c: f64 = points.iter().map(|&(_, c)| c).sum();
+    let sum_sc: f64 = points.iter().map(|&(s, c)| s * c).sum();
+    let sum_s2: f64 = points.iter().map(|&(s, _)| s * s).sum();
+
+    let per_byte = (n * sum_sc - sum_s * sum_c) / (n * sum_s2 - sum_s * sum_s);
+    let fixed = (sum_c - per_byte * sum_s) / n;
+
+    (fixed.max(0.0).round() as u64, per_byte.max(0.0).round() as u64)
+}
+
+/// Plain average of `op`'s measured costs; `0` if none were taken.
+fn average_cost(samples: &[CostSample], op: CryptoOp) -> u64 {
+    let costs: Vec<u64> = samples.iter().filter(|s| s.op == op).map(|s| s.cpu_cost).collect();
+    if costs.is_empty() {
+        return 0;
+    }
+    (costs.iter().sum::<u64>() as f64 / costs.len() as f64).round() as u64
+}
+
+#[cfg(test)]
+mod resource_calibration_tests {
+    use super::*;
+
+    #[test]
+    fn test_from_measurements_fits_exact_linear_model() {
+        // cost = 100 + 2*size, sampled at three distinct sizes.
+        let samples = [
+            CostSample { op: CryptoOp::Sha256, size_bytes: 0, cpu_cost: 100 },
+            CostSample { op: CryptoOp::Sha256, size_bytes: 50, cpu_cost: 200 },
+            CostSample { op: CryptoOp::Sha256, size_bytes: 100, cpu_cost: 300 },
+        ];
+        let calibration = ResourceCalibration::from_measurements(&samples);
+        assert_eq!(calibration.sha256_fixed, 100);
+        assert_eq!(calibration.sha256_per_byte, 2);
+    }
+
+    #[test]
+    fn test_from_measurements_fits_noisy_samples_via_least_squares() {
+        // cost = 10 + 1*size with a little noise; OLS should still land close.
+        let samples = [
+            CostSample { op: CryptoOp::Keccak256, size_bytes: 10, cpu_cost: 21 },
+            CostSample { op: CryptoOp::Keccak256, size_bytes: 20, cpu_cost: 29 },
+            CostSample { op: CryptoOp::Keccak256, size_bytes: 30, cpu_cost: 41 },
+            CostSample { op: CryptoOp::Keccak256, size_bytes: 40, cpu_cost: 49 },
+        ];
+        let calibration = ResourceCalibration::from_measurements(&samples);
+        assert!((calibration.keccak256_fixed as i64 - 10).abs() <= 2);
+        assert!((calibration.keccak256_per_byte as i64 - 1).abs() <= 1);
+    }
+
+    #[test]
+    fn test_from_measurements_single_size_falls_back_to_flat_average() {
+        let samples = [
+            CostSample { op: CryptoOp::Sha256, size_bytes: 32, cpu_cost: 150 },
+            CostSample { op: CryptoOp::Sha256, size_bytes: 32, cpu_cost: 170 },
+        ];
+        let calibration = ResourceCalibration::from_measurements(&samples);
+        assert_eq!(calibration.sha256_fixed, 160, "falls back to the average of same-size samples");
+        assert_eq!(calibration.sha256_per_byte, 0);
+    }
+
+    #[test]
+    fn test_from_measurements_no_samples_for_an_op_yields_zeros() {
+        let calibration = ResourceCalibration::from_measurements(&[]);
+        assert_eq!(calibration.sha256_fixed, 0);
+        assert_eq!(calibration.sha256_per_byte, 0);
+        assert_eq!(calibration.ed25519_fixed, 0);
+    }
+
+    #[test]
+    fn test_from_measurements_averages_ed25519_fixed_cost_samples() {
+        let samples = [
+            CostSample { op: CryptoOp::Ed25519Verify, size_bytes: 64, cpu_cost: 1000 },
+            CostSample { op: CryptoOp::Ed25519Verify, size_bytes: 64, cpu_cost: 1100 },
+            CostSample { op: CryptoOp::Ed25519Verify, size_bytes: 64, cpu_cost: 1200 },
+        ];
+        let calibration = ResourceCalibration::from_measurements(&samples);
+        assert_eq!(calibration.ed25519_fixed, 1100);
+    }
+}
+
 //
 // ───────────────────────────── RESPONSE ─────────────────────────────
 //
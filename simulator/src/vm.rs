@@ -1,28 +1,699 @@
 // Copyright 2025 Erst Users
 // SPDX-License-Identifier: Apache-2.0
 
-use wasmparser::{Operator, Parser, Payload};
+#![allow(clippy::too_many_lines, clippy::match_same_arms)]
+
+use wasmparser::{Operator, Parser, Payload, TypeRef};
+
+/// Toggles for the WASM feature categories Soroban's host environment
+/// cannot execute. Flags are independent rather than a single strict/lenient
+/// switch, since a proposal still under review (or a future, more
+/// permissive host) may only disallow some of these categories.
+#[derive(Debug, Clone, Copy)]
+pub struct SorobanFeatures {
+    pub reject_float: bool,
+    pub reject_simd: bool,
+    pub reject_threads: bool,
+    pub reject_bulk_memory: bool,
+    pub reject_reference_types: bool,
+    pub reject_tail_calls: bool,
+    pub reject_exceptions: bool,
+    /// Additionally rejects float-to-int truncation (whose rounding on
+    /// overflow is host/CPU-dependent) and `memory.grow` (whose success
+    /// depends on host-side memory pressure), both of which can make two
+    /// otherwise-identical validators disagree on contract behavior.
+    pub deterministic: bool,
+}
+
+impl SorobanFeatures {
+    /// The feature set Soroban enforces today: every non-deterministic or
+    /// unsupported category is rejected. This matches (and extends) the
+    /// float-only behavior `enforce_soroban_compatibility` used to provide.
+    pub fn strict() -> Self {
+        Self {
+            reject_float: true,
+            reject_simd: true,
+            reject_threads: true,
+            reject_bulk_memory: true,
+            reject_reference_types: true,
+            reject_tail_calls: true,
+            reject_exceptions: true,
+            deterministic: true,
+        }
+    }
+}
+
+impl Default for SorobanFeatures {
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+/// The WASM feature category an instruction belongs to, for grouping and
+/// rendering [`Violation`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationCategory {
+    FloatingPoint,
+    Simd,
+    Threads,
+    BulkMemory,
+    ReferenceTypes,
+    TailCalls,
+    Exceptions,
+    NonDeterministic,
+}
+
+impl std::fmt::Display for ViolationCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ViolationCategory::FloatingPoint => "floating-point",
+            ViolationCategory::Simd => "SIMD",
+            ViolationCategory::Threads => "threads/atomics",
+            ViolationCategory::BulkMemory => "bulk-memory",
+            ViolationCategory::ReferenceTypes => "reference-types",
+            ViolationCategory::TailCalls => "tail-call",
+            ViolationCategory::Exceptions => "exception-handling",
+            ViolationCategory::NonDeterministic => "non-deterministic",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A single disallowed instruction found during [`validate`], with enough
+/// context for a caller to point a diagnostic at the exact offending opcode
+/// instead of failing the whole module with one generic message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub category: ViolationCategory,
+    /// Debug-rendered instruction, e.g. `F64Add` or `MemoryGrow { mem: 0 }`.
+    pub instruction: String,
+    /// Index of the enclosing function in the module's function index
+    /// space (imported functions included, matching `DW_AT_call_...`/
+    /// `call_indirect` indexing conventions elsewhere in this crate).
+    pub function_index: u32,
+    /// Byte offset of the instruction within the whole module.
+    pub offset: usize,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} instruction `{}` in function #{} at offset {:#x} is not allowed under Soroban compatibility rules",
+            self.category, self.instruction, self.function_index, self.offset
+        )
+    }
+}
+
+/// Validates `wasm` against `features`, walking every function body exactly
+/// once and collecting every violation found rather than bailing on the
+/// first, so a single run reports everything a contract author needs to fix.
+pub fn validate(wasm: &[u8], features: &SorobanFeatures) -> Result<(), Vec<Violation>> {
+    let mut violations = Vec::new();
+    let mut function_index = 0u32;
 
-pub fn enforce_soroban_compatibility(wasm: &[u8]) -> Result<(), String> {
     for payload in Parser::new(0).parse_all(wasm) {
-        let payload = payload.map_err(|e| e.to_string())?;
-        if let Payload::CodeSectionEntry(body) = payload {
-            let mut ops = body.get_operators_reader().map_err(|e| e.to_string())?;
-            while !ops.eof() {
-                let op = ops.read().map_err(|e| e.to_string())?;
-                if is_float_op(&op) {
-                    return Err(
-                        "floating-point instructions are not allowed under strict Soroban compatibility"
-                            .to_string(),
-                    );
+        let Ok(payload) = payload else {
+            break;
+        };
+        match payload {
+            Payload::ImportSection(reader) => {
+                for import in reader.into_iter().flatten() {
+                    if matches!(import.ty, TypeRef::Func(_)) {
+                        function_index += 1;
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                if let Ok(mut ops) = body.get_operators_reader() {
+                    while !ops.eof() {
+                        let op_offset = ops.original_position();
+                        let Ok(op) = ops.read() else {
+                            break;
+                        };
+                        if let Some(category) = classify_violation(&op, features) {
+                            violations.push(Violation {
+                                category,
+                                instruction: format!("{op:?}"),
+                                function_index,
+                                offset: op_offset,
+                            });
+                        }
+                    }
                 }
+                function_index += 1;
             }
+            _ => {}
         }
     }
-    Ok(())
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Backwards-compatible entry point for callers that only need a yes/no
+/// answer under the default (strictest) feature set. New callers that want
+/// configurable categories or the full violation list should use
+/// [`validate`] directly.
+pub fn enforce_soroban_compatibility(wasm: &[u8]) -> Result<(), String> {
+    validate(wasm, &SorobanFeatures::strict()).map_err(|violations| {
+        violations.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    })
 }
 
-fn is_float_op(op: &Operator) -> bool {
-    let rep = format!("{:?}", op);
-    rep.contains("F32") || rep.contains("F64")
+/// Classifies a single instruction against the enabled `features`, returning
+/// the category it violates, or `None` if it's allowed (either because it
+/// doesn't belong to a restricted category, or that category's flag is off).
+fn classify_violation(op: &Operator, features: &SorobanFeatures) -> Option<ViolationCategory> {
+    use Operator::{
+        AtomicFence, Catch, CatchAll, DataDrop, Delegate, ElemDrop, F32Abs, F32Add, F32Ceil, F32Const,
+        F32ConvertI32S, F32ConvertI32U, F32ConvertI64S, F32ConvertI64U, F32Copysign, F32DemoteF64, F32Div, F32Eq,
+        F32Floor, F32Ge, F32Gt, F32Le, F32Load, F32Lt, F32Max, F32Min, F32Mul, F32Ne, F32Nearest, F32Neg,
+        F32ReinterpretI32, F32Sqrt, F32Store, F32Sub, F32Trunc, F64Abs, F64Add, F64Ceil, F64Const, F64ConvertI32S,
+        F64ConvertI32U, F64ConvertI64S, F64ConvertI64U, F64Copysign, F64Div, F64Eq, F64Floor, F64Ge, F64Gt, F64Le,
+        F64Load, F64Lt, F64Max, F64Min, F64Mul, F64Ne, F64Nearest, F64Neg, F64PromoteF32, F64ReinterpretI64, F64Sqrt,
+        F64Store, F64Sub, F64Trunc, I32ReinterpretF32, I32TruncF32S, I32TruncF32U, I32TruncF64S, I32TruncF64U,
+        I32TruncSatF32S, I32TruncSatF32U, I32TruncSatF64S, I32TruncSatF64U, I64ReinterpretF64, I64TruncF32S,
+        I64TruncF32U, I64TruncF64S, I64TruncF64U, I64TruncSatF32S, I64TruncSatF32U, I64TruncSatF64S,
+        I64TruncSatF64U, MemoryAtomicNotify, MemoryAtomicWait32, MemoryAtomicWait64, MemoryCopy, MemoryFill,
+        MemoryGrow, MemoryInit, RefFunc, RefIsNull, RefNull, Rethrow, ReturnCall, ReturnCallIndirect, TableCopy,
+        TableFill, TableGet, TableGrow, TableInit, TableSet, TableSize, Throw, Try,
+        // Atomic load/store/read-modify-write family (threads proposal).
+        I32AtomicLoad, I32AtomicLoad8U, I32AtomicLoad16U, I64AtomicLoad, I64AtomicLoad8U, I64AtomicLoad16U,
+        I64AtomicLoad32U, I32AtomicStore, I32AtomicStore8, I32AtomicStore16, I64AtomicStore, I64AtomicStore8,
+        I64AtomicStore16, I64AtomicStore32, I32AtomicRmwAdd, I32AtomicRmw8AddU, I32AtomicRmw16AddU, I64AtomicRmwAdd,
+        I64AtomicRmw8AddU, I64AtomicRmw16AddU, I64AtomicRmw32AddU, I32AtomicRmwSub, I32AtomicRmw8SubU,
+        I32AtomicRmw16SubU, I64AtomicRmwSub, I64AtomicRmw8SubU, I64AtomicRmw16SubU, I64AtomicRmw32SubU,
+        I32AtomicRmwAnd, I32AtomicRmw8AndU, I32AtomicRmw16AndU, I64AtomicRmwAnd, I64AtomicRmw8AndU,
+        I64AtomicRmw16AndU, I64AtomicRmw32AndU, I32AtomicRmwOr, I32AtomicRmw8OrU, I32AtomicRmw16OrU,
+        I64AtomicRmwOr, I64AtomicRmw8OrU, I64AtomicRmw16OrU, I64AtomicRmw32OrU, I32AtomicRmwXor, I32AtomicRmw8XorU,
+        I32AtomicRmw16XorU, I64AtomicRmwXor, I64AtomicRmw8XorU, I64AtomicRmw16XorU, I64AtomicRmw32XorU,
+        I32AtomicRmwXchg, I32AtomicRmw8XchgU, I32AtomicRmw16XchgU, I64AtomicRmwXchg, I64AtomicRmw8XchgU,
+        I64AtomicRmw16XchgU, I64AtomicRmw32XchgU, I32AtomicRmwCmpxchg, I32AtomicRmw8CmpxchgU,
+        I32AtomicRmw16CmpxchgU, I64AtomicRmwCmpxchg, I64AtomicRmw8CmpxchgU, I64AtomicRmw16CmpxchgU,
+        I64AtomicRmw32CmpxchgU,
+        // SIMD family (fixed-width SIMD proposal).
+        V128Load, V128Load8x8S, V128Load8x8U, V128Load16x4S, V128Load16x4U, V128Load32x2S, V128Load32x2U,
+        V128Load8Splat, V128Load16Splat, V128Load32Splat, V128Load64Splat, V128Load32Zero, V128Load64Zero,
+        V128Store, V128Load8Lane, V128Load16Lane, V128Load32Lane, V128Load64Lane, V128Store8Lane, V128Store16Lane,
+        V128Store32Lane, V128Store64Lane, V128Const, V128Not, V128And, V128AndNot, V128Or, V128Xor, V128Bitselect,
+        V128AnyTrue, I8x16Shuffle, I8x16Swizzle, I8x16Splat, I16x8Splat, I32x4Splat, I64x2Splat, F32x4Splat,
+        F64x2Splat, I8x16ExtractLaneS, I8x16ExtractLaneU, I8x16ReplaceLane, I16x8ExtractLaneS, I16x8ExtractLaneU,
+        I16x8ReplaceLane, I32x4ExtractLane, I32x4ReplaceLane, I64x2ExtractLane, I64x2ReplaceLane, F32x4ExtractLane,
+        F32x4ReplaceLane, F64x2ExtractLane, F64x2ReplaceLane, I8x16Eq, I8x16Ne, I8x16LtS, I8x16LtU, I8x16GtS,
+        I8x16GtU, I8x16LeS, I8x16LeU, I8x16GeS, I8x16GeU, I16x8Eq, I16x8Ne, I16x8LtS, I16x8LtU, I16x8GtS, I16x8GtU,
+        I16x8LeS, I16x8LeU, I16x8GeS, I16x8GeU, I32x4Eq, I32x4Ne, I32x4LtS, I32x4LtU, I32x4GtS, I32x4GtU, I32x4LeS,
+        I32x4LeU, I32x4GeS, I32x4GeU, I64x2Eq, I64x2Ne, I64x2LtS, I64x2GtS, I64x2LeS, I64x2GeS, F32x4Eq, F32x4Ne,
+        F32x4Lt, F32x4Gt, F32x4Le, F32x4Ge, F64x2Eq, F64x2Ne, F64x2Lt, F64x2Gt, F64x2Le, F64x2Ge, I8x16Abs,
+        I8x16Neg, I8x16Popcnt, I8x16AllTrue, I8x16Bitmask, I8x16NarrowI16x8S, I8x16NarrowI16x8U, I8x16Shl,
+        I8x16ShrS, I8x16ShrU, I8x16Add, I8x16AddSatS, I8x16AddSatU, I8x16Sub, I8x16SubSatS, I8x16SubSatU,
+        I8x16MinS, I8x16MinU, I8x16MaxS, I8x16MaxU, I8x16AvgrU, I16x8ExtAddPairwiseI8x16S,
+        I16x8ExtAddPairwiseI8x16U, I16x8Abs, I16x8Neg, I16x8Q15MulrSatS, I16x8AllTrue, I16x8Bitmask,
+        I16x8NarrowI32x4S, I16x8NarrowI32x4U, I16x8ExtendLowI8x16S, I16x8ExtendHighI8x16S, I16x8ExtendLowI8x16U,
+        I16x8ExtendHighI8x16U, I16x8Shl, I16x8ShrS, I16x8ShrU, I16x8Add, I16x8AddSatS, I16x8AddSatU, I16x8Sub,
+        I16x8SubSatS, I16x8SubSatU, I16x8Mul, I16x8MinS, I16x8MinU, I16x8MaxS, I16x8MaxU, I16x8AvgrU,
+        I16x8ExtMulLowI8x16S, I16x8ExtMulHighI8x16S, I16x8ExtMulLowI8x16U, I16x8ExtMulHighI8x16U,
+        I32x4ExtAddPairwiseI16x8S, I32x4ExtAddPairwiseI16x8U, I32x4Abs, I32x4Neg, I32x4AllTrue, I32x4Bitmask,
+        I32x4ExtendLowI16x8S, I32x4ExtendHighI16x8S, I32x4ExtendLowI16x8U, I32x4ExtendHighI16x8U, I32x4Shl,
+        I32x4ShrS, I32x4ShrU, I32x4Add, I32x4Sub, I32x4Mul, I32x4MinS, I32x4MinU, I32x4MaxS, I32x4MaxU,
+        I32x4DotI16x8S, I32x4ExtMulLowI16x8S, I32x4ExtMulHighI16x8S, I32x4ExtMulLowI16x8U, I32x4ExtMulHighI16x8U,
+        I64x2Abs, I64x2Neg, I64x2AllTrue, I64x2Bitmask, I64x2ExtendLowI32x4S, I64x2ExtendHighI32x4S,
+        I64x2ExtendLowI32x4U, I64x2ExtendHighI32x4U, I64x2Shl, I64x2ShrS, I64x2ShrU, I64x2Add, I64x2Sub, I64x2Mul,
+        I64x2ExtMulLowI32x4S, I64x2ExtMulHighI32x4S, I64x2ExtMulLowI32x4U, I64x2ExtMulHighI32x4U, F32x4Ceil,
+        F32x4Floor, F32x4Trunc, F32x4Nearest, F32x4Abs, F32x4Neg, F32x4Sqrt, F32x4Add, F32x4Sub, F32x4Mul,
+        F32x4Div, F32x4Min, F32x4Max, F32x4PMin, F32x4PMax, F64x2Ceil, F64x2Floor, F64x2Trunc, F64x2Nearest,
+        F64x2Abs, F64x2Neg, F64x2Sqrt, F64x2Add, F64x2Sub, F64x2Mul, F64x2Div, F64x2Min, F64x2Max, F64x2PMin,
+        F64x2PMax, I32x4TruncSatF32x4S, I32x4TruncSatF32x4U, F32x4ConvertI32x4S, F32x4ConvertI32x4U,
+        I32x4TruncSatF64x2SZero, I32x4TruncSatF64x2UZero, F64x2ConvertLowI32x4S, F64x2ConvertLowI32x4U,
+        F32x4DemoteF64x2Zero, F64x2PromoteLowF32x4,
+    };
+
+    let is_float_to_int_truncation = matches!(
+        op,
+        I32TruncF32S
+            | I32TruncF32U
+            | I32TruncF64S
+            | I32TruncF64U
+            | I64TruncF32S
+            | I64TruncF32U
+            | I64TruncF64S
+            | I64TruncF64U
+            | I32TruncSatF32S
+            | I32TruncSatF32U
+            | I32TruncSatF64S
+            | I32TruncSatF64U
+            | I64TruncSatF32S
+            | I64TruncSatF32U
+            | I64TruncSatF64S
+            | I64TruncSatF64U
+    );
+    if is_float_to_int_truncation && features.deterministic {
+        return Some(ViolationCategory::NonDeterministic);
+    }
+    if matches!(op, MemoryGrow { .. }) && features.deterministic {
+        return Some(ViolationCategory::NonDeterministic);
+    }
+
+    let is_float_op = matches!(
+        op,
+        F32Load { .. }
+            | F64Load { .. }
+            | F32Store { .. }
+            | F64Store { .. }
+            | F32Const { .. }
+            | F64Const { .. }
+            | F32Eq
+            | F32Ne
+            | F32Lt
+            | F32Gt
+            | F32Le
+            | F32Ge
+            | F64Eq
+            | F64Ne
+            | F64Lt
+            | F64Gt
+            | F64Le
+            | F64Ge
+            | F32Abs
+            | F32Neg
+            | F32Ceil
+            | F32Floor
+            | F32Trunc
+            | F32Nearest
+            | F32Sqrt
+            | F32Add
+            | F32Sub
+            | F32Mul
+            | F32Div
+            | F32Min
+            | F32Max
+            | F32Copysign
+            | F64Abs
+            | F64Neg
+            | F64Ceil
+            | F64Floor
+            | F64Trunc
+            | F64Nearest
+            | F64Sqrt
+            | F64Add
+            | F64Sub
+            | F64Mul
+            | F64Div
+            | F64Min
+            | F64Max
+            | F64Copysign
+            | I32TruncF32S
+            | I32TruncF32U
+            | I32TruncF64S
+            | I32TruncF64U
+            | I64TruncF32S
+            | I64TruncF32U
+            | I64TruncF64S
+            | I64TruncF64U
+            | I32TruncSatF32S
+            | I32TruncSatF32U
+            | I32TruncSatF64S
+            | I32TruncSatF64U
+            | I64TruncSatF32S
+            | I64TruncSatF32U
+            | I64TruncSatF64S
+            | I64TruncSatF64U
+            | F32ConvertI32S
+            | F32ConvertI32U
+            | F32ConvertI64S
+            | F32ConvertI64U
+            | F64ConvertI32S
+            | F64ConvertI32U
+            | F64ConvertI64S
+            | F64ConvertI64U
+            | F32DemoteF64
+            | F64PromoteF32
+            | I32ReinterpretF32
+            | I64ReinterpretF64
+            | F32ReinterpretI32
+            | F64ReinterpretI64
+    );
+    if is_float_op && features.reject_float {
+        return Some(ViolationCategory::FloatingPoint);
+    }
+
+    if features.reject_threads
+        && matches!(
+            op,
+            AtomicFence { .. } | MemoryAtomicNotify { .. } | MemoryAtomicWait32 { .. } | MemoryAtomicWait64 { .. }
+        )
+    {
+        return Some(ViolationCategory::Threads);
+    }
+    // Every other atomic load/store/read-modify-write opcode also gates on
+    // `reject_threads` -- one variant per width/signedness/op combination.
+    if features.reject_threads
+        && matches!(
+            op,
+            I32AtomicLoad { .. }
+                | I32AtomicLoad8U { .. }
+                | I32AtomicLoad16U { .. }
+                | I64AtomicLoad { .. }
+                | I64AtomicLoad8U { .. }
+                | I64AtomicLoad16U { .. }
+                | I64AtomicLoad32U { .. }
+                | I32AtomicStore { .. }
+                | I32AtomicStore8 { .. }
+                | I32AtomicStore16 { .. }
+                | I64AtomicStore { .. }
+                | I64AtomicStore8 { .. }
+                | I64AtomicStore16 { .. }
+                | I64AtomicStore32 { .. }
+                | I32AtomicRmwAdd { .. }
+                | I32AtomicRmw8AddU { .. }
+                | I32AtomicRmw16AddU { .. }
+                | I64AtomicRmwAdd { .. }
+                | I64AtomicRmw8AddU { .. }
+                | I64AtomicRmw16AddU { .. }
+                | I64AtomicRmw32AddU { .. }
+                | I32AtomicRmwSub { .. }
+                | I32AtomicRmw8SubU { .. }
+                | I32AtomicRmw16SubU { .. }
+                | I64AtomicRmwSub { .. }
+                | I64AtomicRmw8SubU { .. }
+                | I64AtomicRmw16SubU { .. }
+                | I64AtomicRmw32SubU { .. }
+                | I32AtomicRmwAnd { .. }
+                | I32AtomicRmw8AndU { .. }
+                | I32AtomicRmw16AndU { .. }
+                | I64AtomicRmwAnd { .. }
+                | I64AtomicRmw8AndU { .. }
+                | I64AtomicRmw16AndU { .. }
+                | I64AtomicRmw32AndU { .. }
+                | I32AtomicRmwOr { .. }
+                | I32AtomicRmw8OrU { .. }
+                | I32AtomicRmw16OrU { .. }
+                | I64AtomicRmwOr { .. }
+                | I64AtomicRmw8OrU { .. }
+                | I64AtomicRmw16OrU { .. }
+                | I64AtomicRmw32OrU { .. }
+                | I32AtomicRmwXor { .. }
+                | I32AtomicRmw8XorU { .. }
+                | I32AtomicRmw16XorU { .. }
+                | I64AtomicRmwXor { .. }
+                | I64AtomicRmw8XorU { .. }
+                | I64AtomicRmw16XorU { .. }
+                | I64AtomicRmw32XorU { .. }
+                | I32AtomicRmwXchg { .. }
+                | I32AtomicRmw8XchgU { .. }
+                | I32AtomicRmw16XchgU { .. }
+                | I64AtomicRmwXchg { .. }
+                | I64AtomicRmw8XchgU { .. }
+                | I64AtomicRmw16XchgU { .. }
+                | I64AtomicRmw32XchgU { .. }
+                | I32AtomicRmwCmpxchg { .. }
+                | I32AtomicRmw8CmpxchgU { .. }
+                | I32AtomicRmw16CmpxchgU { .. }
+                | I64AtomicRmwCmpxchg { .. }
+                | I64AtomicRmw8CmpxchgU { .. }
+                | I64AtomicRmw16CmpxchgU { .. }
+                | I64AtomicRmw32CmpxchgU { .. }
+        )
+    {
+        return Some(ViolationCategory::Threads);
+    }
+
+    if features.reject_bulk_memory
+        && matches!(
+            op,
+            MemoryCopy { .. } | MemoryFill { .. } | MemoryInit { .. } | DataDrop { .. } | TableCopy { .. } | TableInit { .. } | ElemDrop { .. }
+        )
+    {
+        return Some(ViolationCategory::BulkMemory);
+    }
+
+    if features.reject_reference_types
+        && matches!(
+            op,
+            RefNull { .. } | RefIsNull | RefFunc { .. } | TableGet { .. } | TableSet { .. } | TableGrow { .. } | TableSize { .. } | TableFill { .. }
+        )
+    {
+        return Some(ViolationCategory::ReferenceTypes);
+    }
+
+    if features.reject_tail_calls && matches!(op, ReturnCall { .. } | ReturnCallIndirect { .. }) {
+        return Some(ViolationCategory::TailCalls);
+    }
+
+    if features.reject_exceptions
+        && matches!(op, Try { .. } | Catch { .. } | CatchAll | Delegate { .. } | Throw { .. } | Rethrow { .. })
+    {
+        return Some(ViolationCategory::Exceptions);
+    }
+
+    // SIMD carries the largest opcode family -- every fixed-width `V128`/
+    // lane-indexed variant from the SIMD proposal.
+    if features.reject_simd
+        && matches!(
+            op,
+            V128Load { .. }
+                | V128Load8x8S { .. }
+                | V128Load8x8U { .. }
+                | V128Load16x4S { .. }
+                | V128Load16x4U { .. }
+                | V128Load32x2S { .. }
+                | V128Load32x2U { .. }
+                | V128Load8Splat { .. }
+                | V128Load16Splat { .. }
+                | V128Load32Splat { .. }
+                | V128Load64Splat { .. }
+                | V128Load32Zero { .. }
+                | V128Load64Zero { .. }
+                | V128Store { .. }
+                | V128Load8Lane { .. }
+                | V128Load16Lane { .. }
+                | V128Load32Lane { .. }
+                | V128Load64Lane { .. }
+                | V128Store8Lane { .. }
+                | V128Store16Lane { .. }
+                | V128Store32Lane { .. }
+                | V128Store64Lane { .. }
+                | V128Const { .. }
+                | V128Not
+                | V128And
+                | V128AndNot
+                | V128Or
+                | V128Xor
+                | V128Bitselect
+                | V128AnyTrue
+                | I8x16Shuffle { .. }
+                | I8x16Swizzle
+                | I8x16Splat
+                | I16x8Splat
+                | I32x4Splat
+                | I64x2Splat
+                | F32x4Splat
+                | F64x2Splat
+                | I8x16ExtractLaneS { .. }
+                | I8x16ExtractLaneU { .. }
+                | I8x16ReplaceLane { .. }
+                | I16x8ExtractLaneS { .. }
+                | I16x8ExtractLaneU { .. }
+                | I16x8ReplaceLane { .. }
+                | I32x4ExtractLane { .. }
+                | I32x4ReplaceLane { .. }
+                | I64x2ExtractLane { .. }
+                | I64x2ReplaceLane { .. }
+                | F32x4ExtractLane { .. }
+                | F32x4ReplaceLane { .. }
+                | F64x2ExtractLane { .. }
+                | F64x2ReplaceLane { .. }
+                | I8x16Eq
+                | I8x16Ne
+                | I8x16LtS
+                | I8x16LtU
+                | I8x16GtS
+                | I8x16GtU
+                | I8x16LeS
+                | I8x16LeU
+                | I8x16GeS
+                | I8x16GeU
+                | I16x8Eq
+                | I16x8Ne
+                | I16x8LtS
+                | I16x8LtU
+                | I16x8GtS
+                | I16x8GtU
+                | I16x8LeS
+                | I16x8LeU
+                | I16x8GeS
+                | I16x8GeU
+                | I32x4Eq
+                | I32x4Ne
+                | I32x4LtS
+                | I32x4LtU
+                | I32x4GtS
+                | I32x4GtU
+                | I32x4LeS
+                | I32x4LeU
+                | I32x4GeS
+                | I32x4GeU
+                | I64x2Eq
+                | I64x2Ne
+                | I64x2LtS
+                | I64x2GtS
+                | I64x2LeS
+                | I64x2GeS
+                | F32x4Eq
+                | F32x4Ne
+                | F32x4Lt
+                | F32x4Gt
+                | F32x4Le
+                | F32x4Ge
+                | F64x2Eq
+                | F64x2Ne
+                | F64x2Lt
+                | F64x2Gt
+                | F64x2Le
+                | F64x2Ge
+                | I8x16Abs
+                | I8x16Neg
+                | I8x16Popcnt
+                | I8x16AllTrue
+                | I8x16Bitmask
+                | I8x16NarrowI16x8S
+                | I8x16NarrowI16x8U
+                | I8x16Shl
+                | I8x16ShrS
+                | I8x16ShrU
+                | I8x16Add
+                | I8x16AddSatS
+                | I8x16AddSatU
+                | I8x16Sub
+                | I8x16SubSatS
+                | I8x16SubSatU
+                | I8x16MinS
+                | I8x16MinU
+                | I8x16MaxS
+                | I8x16MaxU
+                | I8x16AvgrU
+                | I16x8ExtAddPairwiseI8x16S
+                | I16x8ExtAddPairwiseI8x16U
+                | I16x8Abs
+                | I16x8Neg
+                | I16x8Q15MulrSatS
+                | I16x8AllTrue
+                | I16x8Bitmask
+                | I16x8NarrowI32x4S
+                | I16x8NarrowI32x4U
+                | I16x8ExtendLowI8x16S
+                | I16x8ExtendHighI8x16S
+                | I16x8ExtendLowI8x16U
+                | I16x8ExtendHighI8x16U
+                | I16x8Shl
+                | I16x8ShrS
+                | I16x8ShrU
+                | I16x8Add
+                | I16x8AddSatS
+                | I16x8AddSatU
+                | I16x8Sub
+                | I16x8SubSatS
+                | I16x8SubSatU
+                | I16x8Mul
+                | I16x8MinS
+                | I16x8MinU
+                | I16x8MaxS
+                | I16x8MaxU
+                | I16x8AvgrU
+                | I16x8ExtMulLowI8x16S
+                | I16x8ExtMulHighI8x16S
+                | I16x8ExtMulLowI8x16U
+                | I16x8ExtMulHighI8x16U
+                | I32x4ExtAddPairwiseI16x8S
+                | I32x4ExtAddPairwiseI16x8U
+                | I32x4Abs
+                | I32x4Neg
+                | I32x4AllTrue
+                | I32x4Bitmask
+                | I32x4ExtendLowI16x8S
+                | I32x4ExtendHighI16x8S
+                | I32x4ExtendLowI16x8U
+                | I32x4ExtendHighI16x8U
+                | I32x4Shl
+                | I32x4ShrS
+                | I32x4ShrU
+                | I32x4Add
+                | I32x4Sub
+                | I32x4Mul
+                | I32x4MinS
+                | I32x4MinU
+                | I32x4MaxS
+                | I32x4MaxU
+                | I32x4DotI16x8S
+                | I32x4ExtMulLowI16x8S
+                | I32x4ExtMulHighI16x8S
+                | I32x4ExtMulLowI16x8U
+                | I32x4ExtMulHighI16x8U
+                | I64x2Abs
+                | I64x2Neg
+                | I64x2AllTrue
+                | I64x2Bitmask
+                | I64x2ExtendLowI32x4S
+                | I64x2ExtendHighI32x4S
+                | I64x2ExtendLowI32x4U
+                | I64x2ExtendHighI32x4U
+                | I64x2Shl
+                | I64x2ShrS
+                | I64x2ShrU
+                | I64x2Add
+                | I64x2Sub
+                | I64x2Mul
+                | I64x2ExtMulLowI32x4S
+                | I64x2ExtMulHighI32x4S
+                | I64x2ExtMulLowI32x4U
+                | I64x2ExtMulHighI32x4U
+                | F32x4Ceil
+                | F32x4Floor
+                | F32x4Trunc
+                | F32x4Nearest
+                | F32x4Abs
+                | F32x4Neg
+                | F32x4Sqrt
+                | F32x4Add
+                | F32x4Sub
+                | F32x4Mul
+                | F32x4Div
+                | F32x4Min
+                | F32x4Max
+                | F32x4PMin
+                | F32x4PMax
+                | F64x2Ceil
+                | F64x2Floor
+                | F64x2Trunc
+                | F64x2Nearest
+                | F64x2Abs
+                | F64x2Neg
+                | F64x2Sqrt
+                | F64x2Add
+                | F64x2Sub
+                | F64x2Mul
+                | F64x2Div
+                | F64x2Min
+                | F64x2Max
+                | F64x2PMin
+                | F64x2PMax
+                | I32x4TruncSatF32x4S
+                | I32x4TruncSatF32x4U
+                | F32x4ConvertI32x4S
+                | F32x4ConvertI32x4U
+                | I32x4TruncSatF64x2SZero
+                | I32x4TruncSatF64x2UZero
+                | F64x2ConvertLowI32x4S
+                | F64x2ConvertLowI32x4U
+                | F32x4DemoteF64x2Zero
+                | F64x2PromoteLowF32x4
+        )
+    {
+        return Some(ViolationCategory::Simd);
+    }
+
+    None
 }
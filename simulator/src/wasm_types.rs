@@ -145,49 +145,207 @@ impl SignatureDiff {
             && self.param_mismatches.is_empty()
             && self.result_mismatches.is_empty()
     }
+
+    /// Renders this diff as a rustc-style, multi-line diagnostic comparing
+    /// `expected` (the signature at the `call_indirect` instruction's type
+    /// index) against `actual` (the signature of the function actually found
+    /// in the table slot). Both signatures are printed on their own aligned
+    /// line, with a `^` run under every mismatched parameter/result token
+    /// and a `note:` line spelling out what was expected vs. found at that
+    /// position, plus a trailing note when only the arity differs.
+    pub fn render(&self, expected: &FunctionSignature, actual: &FunctionSignature) -> String {
+        let (expected_line, expected_param_cols, expected_result_cols) = format_with_columns(expected);
+        let (actual_line, actual_param_cols, actual_result_cols) = format_with_columns(actual);
+
+        let mut out = String::new();
+        out.push_str("expected: ");
+        out.push_str(&expected_line);
+        out.push('\n');
+        out.push_str(&caret_line("expected: ".len(), &expected_param_cols, &expected_result_cols, self));
+        out.push_str("   found: ");
+        out.push_str(&actual_line);
+        out.push('\n');
+        out.push_str(&caret_line("   found: ".len(), &actual_param_cols, &actual_result_cols, self));
+
+        for &(index, ref expected_ty, ref actual_ty) in &self.param_mismatches {
+            out.push_str(&format!(
+                "note: parameter #{index}: expected `{expected_ty}` here, found `{actual_ty}` here\n"
+            ));
+        }
+        for &(index, ref expected_ty, ref actual_ty) in &self.result_mismatches {
+            out.push_str(&format!(
+                "note: result #{index}: expected `{expected_ty}` here, found `{actual_ty}` here\n"
+            ));
+        }
+
+        if !self.param_count_match {
+            out.push_str(&format!(
+                "note: parameter count mismatch (expected {}, found {})\n",
+                expected.params.len(),
+                actual.params.len()
+            ));
+        }
+        if !self.result_count_match {
+            out.push_str(&format!(
+                "note: result count mismatch (expected {}, found {})\n",
+                expected.results.len(),
+                actual.results.len()
+            ));
+        }
+
+        out
+    }
+}
+
+/// Formats `sig` the same way as [`FunctionSignature::format`], additionally
+/// returning the starting column of every param/result token within that
+/// string (accounting for the leading `(`, the `, ` separators between
+/// tokens, and the ` -> (` arrow) so [`SignatureDiff::render`] can underline
+/// a mismatched token in place.
+fn format_with_columns(sig: &FunctionSignature) -> (String, Vec<usize>, Vec<usize>) {
+    let mut line = String::from("(");
+    let mut param_cols = Vec::with_capacity(sig.params.len());
+    for (i, ty) in sig.params.iter().enumerate() {
+        if i > 0 {
+            line.push_str(", ");
+        }
+        param_cols.push(line.len());
+        line.push_str(&ty.to_string());
+    }
+    line.push_str(") -> (");
+
+    let mut result_cols = Vec::with_capacity(sig.results.len());
+    for (i, ty) in sig.results.iter().enumerate() {
+        if i > 0 {
+            line.push_str(", ");
+        }
+        result_cols.push(line.len());
+        line.push_str(&ty.to_string());
+    }
+    line.push(')');
+
+    (line, param_cols, result_cols)
+}
+
+/// Builds the caret line underneath one rendered signature: `label_width`
+/// spaces to line up with that signature's own `"expected: "`/`"   found: "`
+/// prefix, then a `^` run starting at each mismatched token's column (from
+/// `param_cols`/`result_cols`, which belong to the same line being
+/// underlined) and spanning that token's own rendered width.
+fn caret_line(label_width: usize, param_cols: &[usize], result_cols: &[usize], diff: &SignatureDiff) -> String {
+    let mut carets: Vec<(usize, usize)> = Vec::new();
+    for &(index, ref expected_ty, ref actual_ty) in &diff.param_mismatches {
+        if let Some(&col) = param_cols.get(index) {
+            carets.push((col, expected_ty.to_string().len().max(actual_ty.to_string().len())));
+        }
+    }
+    for &(index, ref expected_ty, ref actual_ty) in &diff.result_mismatches {
+        if let Some(&col) = result_cols.get(index) {
+            carets.push((col, expected_ty.to_string().len().max(actual_ty.to_string().len())));
+        }
+    }
+    carets.sort_by_key(|&(col, _)| col);
+
+    if carets.is_empty() {
+        return String::new();
+    }
+
+    let mut line = " ".repeat(label_width);
+    for (col, width) in carets {
+        while line.len() < label_width + col {
+            line.push(' ');
+        }
+        line.push_str(&"^".repeat(width));
+    }
+    line.push('\n');
+    line
 }
 
-/// Parsed type section containing function signatures
+/// Parsed type section containing function signatures, plus enough of the
+/// import/function/table/element sections to resolve the concrete
+/// signature sitting in any table slot -- the "actual" side of a
+/// `call_indirect` signature mismatch, which the type section alone can't
+/// answer (it only knows what the instruction *expects*).
 #[derive(Debug, Clone)]
 pub struct TypeSection {
     types: Vec<FunctionSignature>,
+    /// Function index (imported functions first, then module-defined ones,
+    /// matching the module's function index space) -> its type index.
+    function_types: Vec<u32>,
+    /// Table index -> slot -> the func index an active element segment put
+    /// there, or `None` for a slot no segment ever initialized (a trap on
+    /// `call_indirect` against it is a null-funcref trap, not a signature
+    /// mismatch).
+    table_slots: Vec<Vec<Option<u32>>>,
 }
 
 impl TypeSection {
-    /// Parse the type section from WebAssembly module bytes
+    /// Parse the type, import, function, table, and element sections from
+    /// WebAssembly module bytes.
     pub fn parse(wasm_bytes: &[u8]) -> Result<Self, String> {
         let mut types = Vec::new();
+        let mut function_types = Vec::new();
+        let mut table_slots: Vec<Vec<Option<u32>>> = Vec::new();
 
         for payload in Parser::new(0).parse_all(wasm_bytes) {
             let payload = payload.map_err(|e| format!("Failed to parse WASM: {}", e))?;
 
-            if let Payload::TypeSection(type_reader) = payload {
-                for rec_group in type_reader {
-                    let rec_group = rec_group.map_err(|e| format!("Failed to read type: {}", e))?;
-
-                    // RecGroup contains SubType entries
-                    for sub_type in rec_group.types() {
-                        if let wasmparser::CompositeType::Func(func_type) = &sub_type.composite_type {
-                            let params = func_type
-                                .params()
-                                .iter()
-                                .map(|vt| ValueType::from_valtype(*vt))
-                                .collect();
-
-                            let results = func_type
-                                .results()
-                                .iter()
-                                .map(|vt| ValueType::from_valtype(*vt))
-                                .collect();
-
-                            types.push(FunctionSignature::new(params, results));
+            match payload {
+                Payload::TypeSection(type_reader) => {
+                    for rec_group in type_reader {
+                        let rec_group = rec_group.map_err(|e| format!("Failed to read type: {}", e))?;
+
+                        // RecGroup contains SubType entries
+                        for sub_type in rec_group.types() {
+                            if let wasmparser::CompositeType::Func(func_type) = &sub_type.composite_type {
+                                let params = func_type
+                                    .params()
+                                    .iter()
+                                    .map(|vt| ValueType::from_valtype(*vt))
+                                    .collect();
+
+                                let results = func_type
+                                    .results()
+                                    .iter()
+                                    .map(|vt| ValueType::from_valtype(*vt))
+                                    .collect();
+
+                                types.push(FunctionSignature::new(params, results));
+                            }
                         }
                     }
                 }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import.map_err(|e| format!("Failed to read import: {}", e))?;
+                        if let wasmparser::TypeRef::Func(type_index) = import.ty {
+                            function_types.push(type_index);
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for type_index in reader {
+                        let type_index = type_index.map_err(|e| format!("Failed to read function: {}", e))?;
+                        function_types.push(type_index);
+                    }
+                }
+                Payload::TableSection(reader) => {
+                    for table in reader {
+                        let table = table.map_err(|e| format!("Failed to read table: {}", e))?;
+                        table_slots.push(vec![None; table.ty.initial as usize]);
+                    }
+                }
+                Payload::ElementSection(reader) => {
+                    for element in reader {
+                        let element = element.map_err(|e| format!("Failed to read element segment: {}", e))?;
+                        populate_element_segment(&element, &mut table_slots)?;
+                    }
+                }
+                _ => {}
             }
         }
 
-        Ok(TypeSection { types })
+        Ok(TypeSection { types, function_types, table_slots })
     }
 
     /// Get a function signature by type index
@@ -205,6 +363,67 @@ impl TypeSection {
     pub fn is_empty(&self) -> bool {
         self.types.is_empty()
     }
+
+    /// Resolves the concrete [`FunctionSignature`] actually sitting in
+    /// `table`'s `slot`, by following slot -> func index -> type index ->
+    /// signature. Returns `None` both when `table`/`slot` is out of range
+    /// and when the slot is a never-initialized (null funcref) entry --
+    /// either way there's no signature to compare against, just a different
+    /// trap (`call_indirect` on an out-of-bounds or null slot, rather than a
+    /// signature mismatch).
+    pub fn signature_at_table_slot(&self, table: u32, slot: u32) -> Option<&FunctionSignature> {
+        let func_index = (*self.table_slots.get(table as usize)?.get(slot as usize)?)?;
+        let type_index = *self.function_types.get(func_index as usize)?;
+        self.get_signature(type_index)
+    }
+}
+
+/// Materializes one active element segment's func indices into
+/// `table_slots`, starting at its constant `i32` offset. Passive/declared
+/// segments (not tied to a table at instantiation time) and non-constant or
+/// `ref.func`-expression items are skipped -- this index only needs to
+/// answer "what's in slot N today", which active segments with a literal
+/// function-index list fully determine.
+fn populate_element_segment(
+    element: &wasmparser::Element,
+    table_slots: &mut [Vec<Option<u32>>],
+) -> Result<(), String> {
+    let wasmparser::ElementKind::Active { table_index, offset_expr } = &element.kind else {
+        return Ok(());
+    };
+    let table_index = table_index.unwrap_or(0) as usize;
+
+    let Some(offset) = eval_const_i32(offset_expr) else {
+        return Ok(());
+    };
+
+    let wasmparser::ElementItems::Functions(funcs) = &element.items else {
+        return Ok(());
+    };
+
+    let Some(slots) = table_slots.get_mut(table_index) else {
+        return Ok(());
+    };
+    for (i, func_index) in funcs.clone().into_iter().enumerate() {
+        let func_index = func_index.map_err(|e| format!("Failed to read element function index: {}", e))?;
+        let target = offset as usize + i;
+        if target < slots.len() {
+            slots[target] = Some(func_index);
+        }
+    }
+    Ok(())
+}
+
+/// Evaluates a `.debug`-free constant offset expression (`i32.const N`) --
+/// the only shape an element segment's offset takes in practice outside of
+/// the `GlobalGet` form used by some linkers, which isn't resolvable
+/// without also tracking the global section and is left unsupported.
+fn eval_const_i32(expr: &wasmparser::ConstExpr) -> Option<i32> {
+    let mut reader = expr.get_operators_reader();
+    match reader.read().ok()? {
+        wasmparser::Operator::I32Const { value } => Some(value),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -305,6 +524,53 @@ mod tests {
         assert_eq!(diff.result_mismatches[0].2, ValueType::I32);
     }
 
+    #[test]
+    fn test_render_underlines_mismatched_param() {
+        let expected = FunctionSignature::new(vec![ValueType::I32, ValueType::I64], vec![ValueType::I32]);
+        let actual = FunctionSignature::new(vec![ValueType::I32, ValueType::I32], vec![ValueType::I32]);
+        let diff = expected.compare(&actual);
+
+        let rendered = diff.render(&expected, &actual);
+        assert!(rendered.contains("expected: (i32, i64) -> (i32)"));
+        assert!(rendered.contains("   found: (i32, i32) -> (i32)"));
+        // "i64"/"i32" both start at column 6 of their `(...)` line, right
+        // after "expected: "/"   found: " (10 chars) + "(i32, " (6 chars).
+        assert!(rendered.contains(&format!("{}^^^\n", " ".repeat(10 + 6))));
+        assert!(rendered.contains("note: parameter #1: expected `i64` here, found `i32` here"));
+        assert!(!rendered.contains("parameter count mismatch"));
+    }
+
+    #[test]
+    fn test_render_underlines_mismatched_result() {
+        let expected = FunctionSignature::new(vec![ValueType::I32], vec![ValueType::I64]);
+        let actual = FunctionSignature::new(vec![ValueType::I32], vec![ValueType::I32]);
+        let diff = expected.compare(&actual);
+
+        let rendered = diff.render(&expected, &actual);
+        assert!(rendered.contains("note: result #0: expected `i64` here, found `i32` here"));
+        assert!(!rendered.contains("result count mismatch"));
+    }
+
+    #[test]
+    fn test_render_notes_count_mismatch_without_carets() {
+        let expected = FunctionSignature::new(vec![ValueType::I32], vec![ValueType::I64]);
+        let actual = FunctionSignature::new(vec![ValueType::I32, ValueType::I32], vec![ValueType::I64]);
+        let diff = expected.compare(&actual);
+
+        let rendered = diff.render(&expected, &actual);
+        assert!(rendered.contains("note: parameter count mismatch (expected 1, found 2)"));
+        assert!(diff.param_mismatches.is_empty(), "shared prefix still matches type-for-type");
+    }
+
+    #[test]
+    fn test_render_matching_signatures_has_no_notes() {
+        let sig = FunctionSignature::new(vec![ValueType::I32], vec![ValueType::I64]);
+        let diff = sig.compare(&sig);
+        let rendered = sig.compare(&sig).render(&sig, &sig);
+        assert!(diff.is_match());
+        assert!(!rendered.contains("note:"));
+    }
+
     #[test]
     fn test_type_section_parse_simple_module() {
         // Simple WAT: (module (func (param i32) (result i64)))
@@ -345,4 +611,95 @@ mod tests {
         let type_section = TypeSection::parse(&wasm).unwrap();
         assert!(type_section.get_signature(10).is_none());
     }
+
+    #[test]
+    fn test_signature_at_table_slot_resolves_elements() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (type (func (param i32) (result i64)))
+                (type (func (param i32 i32) (result i32)))
+                (func $f0 (type 0) (param i32) (result i64) i64.const 0)
+                (func $f1 (type 1) (param i32 i32) (result i32) i32.const 0)
+                (table 2 funcref)
+                (elem (i32.const 0) $f0 $f1)
+            )
+            "#,
+        )
+        .unwrap();
+        let type_section = TypeSection::parse(&wasm).unwrap();
+
+        let slot0 = type_section.signature_at_table_slot(0, 0).unwrap();
+        assert_eq!(slot0.params, vec![ValueType::I32]);
+        assert_eq!(slot0.results, vec![ValueType::I64]);
+
+        let slot1 = type_section.signature_at_table_slot(0, 1).unwrap();
+        assert_eq!(slot1.params, vec![ValueType::I32, ValueType::I32]);
+        assert_eq!(slot1.results, vec![ValueType::I32]);
+    }
+
+    #[test]
+    fn test_signature_at_table_slot_accounts_for_imported_functions() {
+        // The imported function occupies function index 0, so the element
+        // segment's second entry (the module-defined function) must resolve
+        // to function index 1, not 0.
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (type (func (param i32) (result i64)))
+                (import "env" "host_fn" (func $h (type 0)))
+                (func $f (type 0) (param i32) (result i64) i64.const 0)
+                (table 2 funcref)
+                (elem (i32.const 0) $h $f)
+            )
+            "#,
+        )
+        .unwrap();
+        let type_section = TypeSection::parse(&wasm).unwrap();
+
+        assert!(type_section.signature_at_table_slot(0, 0).is_some());
+        assert!(type_section.signature_at_table_slot(0, 1).is_some());
+    }
+
+    #[test]
+    fn test_signature_at_table_slot_uninitialized_slot_is_none() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (type (func (param i32) (result i64)))
+                (func $f0 (type 0) (param i32) (result i64) i64.const 0)
+                (table 3 funcref)
+                (elem (i32.const 0) $f0)
+            )
+            "#,
+        )
+        .unwrap();
+        let type_section = TypeSection::parse(&wasm).unwrap();
+
+        assert!(type_section.signature_at_table_slot(0, 0).is_some());
+        assert!(
+            type_section.signature_at_table_slot(0, 1).is_none(),
+            "slot never populated by an element segment is a null funcref, not a signature"
+        );
+        assert!(type_section.signature_at_table_slot(0, 2).is_none());
+    }
+
+    #[test]
+    fn test_signature_at_table_slot_out_of_range_table_or_slot_is_none() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (type (func (param i32) (result i64)))
+                (func $f0 (type 0) (param i32) (result i64) i64.const 0)
+                (table 1 funcref)
+                (elem (i32.const 0) $f0)
+            )
+            "#,
+        )
+        .unwrap();
+        let type_section = TypeSection::parse(&wasm).unwrap();
+
+        assert!(type_section.signature_at_table_slot(5, 0).is_none(), "no table 5 exists");
+        assert!(type_section.signature_at_table_slot(0, 100).is_none(), "slot 100 is out of the table's bounds");
+    }
 }
@@ -24,6 +24,8 @@ use simulator::source_mapper::SourceMapper;
 
 // Address in the fixture that maps to src/test.rs:42
 const CRASH_ADDR: u64 = 0x1000;
+// Column the fixture's single row carries at CRASH_ADDR.
+const CRASH_COLUMN: u64 = 9;
 
 // ─── Fixture builder ────────────────────────────────────────────────────────
 
@@ -72,6 +74,7 @@ fn build_debug_line_section() -> Vec<u8> {
     program.begin_sequence(Some(Address::Constant(CRASH_ADDR)));
     program.row().file = file_id;
     program.row().line = 42;
+    program.row().column = CRASH_COLUMN;
     program.generate_row();
     program.end_sequence(4); // sequence spans 4 bytes past the first instruction
 
@@ -184,6 +187,7 @@ fn source_map_known_crash_wasm_yields_src_test_rs_42() {
 
     assert_eq!(loc.file, "src/test.rs", "wrong source file");
     assert_eq!(loc.line, 42, "wrong line number");
+    assert_eq!(loc.column, Some(CRASH_COLUMN as u32), "wrong column number");
 }
 
 /// Cross-validation: parse the fixture's DWARF content directly with `gimli`
@@ -235,6 +239,17 @@ fn wasm_fixture_dwarf_content_is_canonical() {
                 "expected line 42 at address {:#x}",
                 CRASH_ADDR
             );
+            let column = match row.column() {
+                gimli::ColumnType::LeftEdge => None,
+                gimli::ColumnType::Column(c) => Some(c.get()),
+            };
+            assert_eq!(
+                column,
+                Some(CRASH_COLUMN),
+                "expected column {} at address {:#x}",
+                CRASH_COLUMN,
+                CRASH_ADDR
+            );
 
             found = true;
             break;
@@ -248,6 +263,23 @@ fn wasm_fixture_dwarf_content_is_canonical() {
     );
 }
 
+/// `with_path_remap` replaces the fixture's `src` prefix with `/mapped`,
+/// proving the remap is applied to the resolved `SourceLocation.file` while
+/// the line is untouched.
+#[test]
+fn with_path_remap_rewrites_resolved_file_prefix() {
+    let wasm = build_wasm_fixture();
+    let mapper = SourceMapper::new_without_cache(wasm)
+        .with_path_remap(vec![("src".to_string(), "/mapped".to_string())]);
+
+    let loc = mapper
+        .map_wasm_offset_to_source(CRASH_ADDR)
+        .expect("must resolve a source location for the known crash offset");
+
+    assert_eq!(loc.file, "/mapped/test.rs", "remapped prefix must replace the original one");
+    assert_eq!(loc.line, 42, "remap must not affect the resolved line number");
+}
+
 /// Control: a WASM without debug sections must yield `None` from both
 /// `has_debug_symbols` and `map_wasm_offset_to_source`.
 #[test]